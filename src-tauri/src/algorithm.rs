@@ -1,2 +1,9 @@
 pub mod aco;
+pub mod annealing;
+pub mod constraint;
+pub mod genetic;
+pub mod local_search;
+pub mod runner;
+pub mod solver;
+pub mod stop_criteria;
 pub mod time_table;
@@ -3,4 +3,9 @@ pub mod aco_solver;
 mod ant;
 pub mod colony;
 pub mod graph;
+pub mod ordering;
+pub mod parameter_preset;
+pub mod pheromone;
+mod pheromone_table;
+pub mod stagnation;
 pub mod violations;
@@ -1,14 +1,25 @@
+use super::aco_solver::MAX_SEED;
+use super::ordering::ClassOrdering;
+use super::pheromone::PheromoneSettings;
+use super::stagnation::StagnationSettings;
+use crate::algorithm::annealing::AnnealingSettings;
+use crate::algorithm::genetic::GeneticSettings;
+use crate::algorithm::local_search::LocalSearchSettings;
+use crate::algorithm::solver::SolverKind;
+use crate::input::Input;
+use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use tauri::Manager;
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct AcoParameters {
     pub num_of_ants: usize,
     pub num_of_classes: usize,
     pub num_of_rooms: usize,
     pub num_of_periods: usize,
-    pub num_of_day_lengths: usize,
     pub num_of_teachers: usize,
     pub num_of_students: usize,
+    //授業を固める枠の時限数。1日の長さにして、枠が日をまたがないようにする
     pub size_of_frame: usize,
     pub q: f64,
     pub alpha: f64,
@@ -19,28 +30,322 @@ pub struct AcoParameters {
     pub tau_max: f64,
     pub ant_prob_random: f64,
     pub super_not_change: usize,
+    //蟻を作るスレッドの数。0ならCPUの数
+    #[serde(default)]
+    pub num_of_threads: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub weights: ConstraintWeights,
+    #[serde(default)]
+    pub local_search: LocalSearchSettings,
+    #[serde(default)]
+    pub solver: SolverKind,
+    #[serde(default)]
+    pub annealing: AnnealingSettings,
+    #[serde(default)]
+    pub genetic: GeneticSettings,
+    #[serde(default)]
+    pub pheromone: PheromoneSettings,
+    #[serde(default)]
+    pub stagnation: StagnationSettings,
+    #[serde(default)]
+    pub class_ordering: ClassOrdering,
+}
+
+//各制約違反1件あたりの重み。経路長(評価値)に足される
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConstraintWeights {
+    pub capacity: f64,
+    pub teacher: f64,
+    pub student: f64,
+    pub absent_days: f64,
+    pub straddle_days: f64,
+    //枠内の空き時限
+    pub collection: f64,
+    pub room_not_candidate: f64,
+    //置ける場所がなく置かなかった授業
+    pub unassigned: f64,
+}
+
+impl Default for ConstraintWeights {
+    fn default() -> Self {
+        ConstraintWeights {
+            capacity: 2.0,
+            teacher: 5.0,
+            student: 3.0,
+            absent_days: 3.0,
+            straddle_days: 1.0,
+            collection: 1.0,
+            room_not_candidate: 10.0,
+            unassigned: 100.0,
+        }
+    }
+}
+
+impl AcoParameters {
+    //件数は入力から、それ以外は設定値から作る
+    pub fn new(settings: &AcoSettings, input: &Input) -> AcoParameters {
+        AcoParameters {
+            num_of_ants: settings.num_of_ants,
+            num_of_classes: input.get_classes().len(),
+            num_of_rooms: input.get_rooms().len(),
+            num_of_periods: input.get_calendar().num_of_periods(),
+            num_of_teachers: input.get_teachers().len(),
+            num_of_students: input.get_student_groups().len(),
+            size_of_frame: input.get_calendar().max_day_length().max(1),
+            q: settings.q,
+            alpha: settings.alpha,
+            beta: settings.beta,
+            rou: settings.rou,
+            max_iterations: settings.max_iterations,
+            tau_min: settings.tau_min,
+            tau_max: settings.tau_max,
+            ant_prob_random: settings.ant_prob_random,
+            super_not_change: settings.super_not_change,
+            num_of_threads: settings.num_of_threads,
+            seed: settings.seed,
+            weights: settings.weights.clone(),
+            local_search: settings.local_search.clone(),
+            solver: settings.solver,
+            annealing: settings.annealing.clone(),
+            genetic: settings.genetic.clone(),
+            pheromone: settings.pheromone.clone(),
+            stagnation: settings.stagnation.clone(),
+            class_ordering: settings.class_ordering,
+        }
+    }
+
+    pub fn get_settings(&self) -> AcoSettings {
+        AcoSettings {
+            num_of_ants: self.num_of_ants,
+            q: self.q,
+            alpha: self.alpha,
+            beta: self.beta,
+            rou: self.rou,
+            max_iterations: self.max_iterations,
+            tau_min: self.tau_min,
+            tau_max: self.tau_max,
+            ant_prob_random: self.ant_prob_random,
+            super_not_change: self.super_not_change,
+            num_of_threads: self.num_of_threads,
+            seed: self.seed,
+            weights: self.weights.clone(),
+            local_search: self.local_search.clone(),
+            solver: self.solver,
+            annealing: self.annealing.clone(),
+            genetic: self.genetic.clone(),
+            pheromone: self.pheromone.clone(),
+            stagnation: self.stagnation.clone(),
+            class_ordering: self.class_ordering,
+        }
+    }
+}
+
+//UIから変更できる値。データセットに依存する件数は含まない
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcoSettings {
+    pub num_of_ants: usize,
+    pub q: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    //フェロモンの残存率
+    pub rou: f64,
+    pub max_iterations: usize,
+    pub tau_min: f64,
+    pub tau_max: f64,
+    pub ant_prob_random: f64,
+    pub super_not_change: usize,
+    //蟻を作るスレッドの数。0ならCPUの数
+    #[serde(default)]
+    pub num_of_threads: usize,
+    //Noneなら実行ごとに新しいシードを使う
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub weights: ConstraintWeights,
+    #[serde(default)]
+    pub local_search: LocalSearchSettings,
+    #[serde(default)]
+    pub solver: SolverKind,
+    #[serde(default)]
+    pub annealing: AnnealingSettings,
+    #[serde(default)]
+    pub genetic: GeneticSettings,
+    #[serde(default)]
+    pub pheromone: PheromoneSettings,
+    #[serde(default)]
+    pub stagnation: StagnationSettings,
+    #[serde(default)]
+    pub class_ordering: ClassOrdering,
+}
+
+impl Default for AcoSettings {
+    fn default() -> Self {
+        AcoSettings {
+            num_of_ants: 3,
+            q: 10.0,
+            alpha: 1.0,
+            beta: 1.0,
+            rou: 0.5,
+            max_iterations: 10000,
+            tau_min: 0.001,
+            tau_max: 100000.0,
+            ant_prob_random: 0.0,
+            super_not_change: 500,
+            num_of_threads: 0,
+            seed: None,
+            weights: ConstraintWeights::default(),
+            local_search: LocalSearchSettings::default(),
+            solver: SolverKind::default(),
+            annealing: AnnealingSettings::default(),
+            genetic: GeneticSettings::default(),
+            pheromone: PheromoneSettings::default(),
+            stagnation: StagnationSettings::default(),
+            class_ordering: ClassOrdering::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterError {
+    pub field: String,
+    pub value: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}: {}", self.field, self.value, self.message)
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+pub fn check_usize(
+    errors: &mut Vec<ParameterError>,
+    field: &str,
+    value: usize,
+    min: usize,
+    max: usize,
+) {
+    if value < min || value > max {
+        errors.push(ParameterError {
+            field: field.to_string(),
+            value: value.to_string(),
+            message: format!("must be between {} and {}", min, max),
+        });
+    }
+}
+
+pub fn check_f64(errors: &mut Vec<ParameterError>, field: &str, value: f64, min: f64, max: f64) {
+    if !value.is_finite() || value < min || value > max {
+        errors.push(ParameterError {
+            field: field.to_string(),
+            value: value.to_string(),
+            message: format!("must be between {} and {}", min, max),
+        });
+    }
+}
+
+impl AcoSettings {
+    pub fn validate(&self) -> Result<(), Vec<ParameterError>> {
+        let mut errors = Vec::new();
+        check_usize(&mut errors, "numOfAnts", self.num_of_ants, 1, 1000);
+        check_f64(&mut errors, "q", self.q, f64::MIN_POSITIVE, f64::MAX);
+        check_f64(&mut errors, "alpha", self.alpha, 0.0, 100.0);
+        check_f64(&mut errors, "beta", self.beta, 0.0, 100.0);
+        check_f64(&mut errors, "rou", self.rou, 0.0, 1.0);
+        check_usize(
+            &mut errors,
+            "maxIterations",
+            self.max_iterations,
+            1,
+            10_000_000,
+        );
+        check_f64(&mut errors, "tauMin", self.tau_min, 0.0, f64::MAX);
+        check_f64(&mut errors, "tauMax", self.tau_max, 0.0, f64::MAX);
+        if self.tau_min >= self.tau_max {
+            errors.push(ParameterError {
+                field: "tauMax".to_string(),
+                value: self.tau_max.to_string(),
+                message: format!("must be greater than tauMin ({})", self.tau_min),
+            });
+        }
+        check_f64(&mut errors, "antProbRandom", self.ant_prob_random, 0.0, 1.0);
+        check_usize(
+            &mut errors,
+            "superNotChange",
+            self.super_not_change,
+            1,
+            usize::MAX,
+        );
+        check_usize(&mut errors, "numOfThreads", self.num_of_threads, 0, 1024);
+        let weights = [
+            ("weights.capacity", self.weights.capacity),
+            ("weights.teacher", self.weights.teacher),
+            ("weights.student", self.weights.student),
+            ("weights.absentDays", self.weights.absent_days),
+            ("weights.straddleDays", self.weights.straddle_days),
+            ("weights.collection", self.weights.collection),
+            ("weights.roomNotCandidate", self.weights.room_not_candidate),
+            ("weights.unassigned", self.weights.unassigned),
+        ];
+        for (field, value) in weights {
+            check_f64(&mut errors, field, value, 0.0, 1000.0);
+        }
+        check_usize(
+            &mut errors,
+            "localSearch.maxSteps",
+            self.local_search.max_steps,
+            1,
+            1_000_000,
+        );
+        self.annealing.validate(&mut errors);
+        self.genetic.validate(&mut errors);
+        self.pheromone.validate(&mut errors);
+        self.stagnation.validate(&mut errors);
+        //usizeに変換すると32bitの環境で切り捨てられるので、u64のまま比べる
+        if let Some(seed) = self.seed {
+            if seed > MAX_SEED {
+                errors.push(ParameterError {
+                    field: "seed".to_string(),
+                    value: seed.to_string(),
+                    message: format!("must be between 0 and {}", MAX_SEED),
+                });
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(())
+    }
 }
 
 pub struct AcoParametersManager {
+    //現在のソルバーが使っている値
     pub parameters: Mutex<Option<AcoParameters>>,
+    //次にソルバーを作るときに使う値
+    pub settings: Mutex<AcoSettings>,
+}
+
+#[tauri::command]
+pub fn handle_get_parameters(
+    aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
+) -> Result<AcoSettings, String> {
+    Ok(aco_parameters_manager.settings.lock().unwrap().clone())
 }
 
-static days_of_week: [&str; 7] = ["月", "火", "水", "木", "金", "土", "日"];
 #[tauri::command]
-pub fn handle_get_periods(
-    parameters_manager: tauri::State<'_, AcoParametersManager>,
-) -> Result<Vec<String>, String> {
-    let parameters = parameters_manager.parameters.lock().unwrap();
-    let mut res = Vec::new();
-    if let Some(parameters) = &*parameters {
-        for i in 0..parameters.num_of_periods {
-            res.push(format!(
-                "{}曜日 {}限",
-                days_of_week[(i / parameters.num_of_day_lengths) % days_of_week.len()],
-                i % parameters.num_of_day_lengths + 1
-            ));
-        }
-        return Ok(res);
-    }
-    Err("No parameters".to_string())
+pub fn handle_set_parameters(
+    aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
+    settings: AcoSettings,
+) -> Result<AcoSettings, Vec<ParameterError>> {
+    println!("called handle_set_parameters {:?}", settings);
+    settings.validate()?;
+    *aco_parameters_manager.settings.lock().unwrap() = settings.clone();
+    Ok(settings)
 }
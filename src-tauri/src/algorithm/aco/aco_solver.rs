@@ -3,14 +3,28 @@ use super::aco_parameters::AcoParameters;
 use super::ant::Ant;
 use super::colony::Colony;
 use super::graph::Graph;
+use super::stagnation::{Restart, RestartMode, StagnationReason};
 use super::violations::Violations;
-use crate::{
-    algorithm::time_table::cell::ActiveCell,
-    input::{class, Input},
+use crate::algorithm::constraint::{
+    ConstraintContext, ConstraintKind, Placements, Score, ScoreBreakdown, CONSTRAINTS,
 };
-use std::sync::Mutex;
+use crate::algorithm::local_search::{LocalSearchMode, LocalSearchResult};
+use crate::algorithm::solver::{Solver, SolverKind, SolverManager};
+use crate::input::{class, Input};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use tauri::Manager;
 
+//乱数はすべてソルバーが持つこの1つから引く。同じシードと入力なら同じ結果になる
+pub type SolverRng = StdRng;
+
+//UIのnumberで欠けないよう53bitに収める
+pub const MAX_SEED: u64 = (1 << 53) - 1;
+
+pub fn new_seed() -> u64 {
+    rand::random::<u64>() & MAX_SEED
+}
+
 #[derive(Clone)]
 pub struct ACOSolver {
     pub parameters: AcoParameters,
@@ -18,15 +32,50 @@ pub struct ACOSolver {
     pub best_ant: Option<Ant>,
     pub super_ant: Option<Ant>,
     pub cnt_super_not_change: usize,
+    pub iteration: usize,
+    //直近の世代のフェロモンのエントロピー
+    pub entropy: f64,
+    pub restarts: Vec<Restart>,
+    //最後に再出発した世代
+    pub last_restart: Option<usize>,
     pub input: Input,
+    pub seed: u64,
+    rng: SolverRng,
 }
 
 impl ACOSolver {
-    pub fn run_aco(&mut self, graph: &super::graph::Graph) {
+    //シードの指定がなければ新しく決めて、parametersに記録する
+    pub fn new(mut parameters: AcoParameters, input: Input) -> ACOSolver {
+        let seed = parameters.seed.unwrap_or_else(new_seed);
+        parameters.seed = Some(seed);
+        let graph = Graph::new(
+            parameters.clone(),
+            input.get_classes().clone(),
+            input.get_rooms().clone(),
+            input.get_teachers().clone(),
+            input.get_calendar().clone(),
+        );
+        ACOSolver {
+            parameters: parameters.clone(),
+            colony: Colony::new(graph, parameters),
+            best_ant: None,
+            super_ant: None,
+            cnt_super_not_change: 0,
+            iteration: 0,
+            entropy: 1.0,
+            restarts: Vec::new(),
+            last_restart: None,
+            input,
+            seed,
+            rng: SolverRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn run_aco(&mut self) {
         for _ in 0..self.parameters.max_iterations {
             self.update_aco();
             if let Some(best_ant) = &self.best_ant {
-                println!("best path length: {}", best_ant.calc_all_path_length(graph));
+                println!("best path length: {}", best_ant.calc_all_path_length());
             }
         }
     }
@@ -37,8 +86,11 @@ impl ACOSolver {
             self.parameters.num_of_rooms as usize
         ];
         if let Some(ant) = &self.best_ant {
-            for (class_id, &[room_id, period_id]) in ant.get_corresponding_crp().iter().enumerate()
-            {
+            for (class_id, slot) in ant.get_placements().get_slots().iter().enumerate() {
+                let [room_id, period_id] = match slot {
+                    Some(slot) => *slot,
+                    None => continue,
+                };
                 for i in 0..self.input.get_classes()[class_id].serial_size {
                     res[room_id as usize][period_id as usize + i as usize] = class_id;
                 }
@@ -53,8 +105,11 @@ impl ACOSolver {
             self.parameters.num_of_rooms as usize
         ];
         if let Some(ant) = &self.best_ant {
-            for (class_id, &[room_id, period_id]) in ant.get_corresponding_crp().iter().enumerate()
-            {
+            for (class_id, slot) in ant.get_placements().get_slots().iter().enumerate() {
+                let [room_id, period_id] = match slot {
+                    Some(slot) => *slot,
+                    None => continue,
+                };
                 res[room_id as usize][period_id as usize] = self
                     .colony
                     .get_graph()
@@ -67,7 +122,6 @@ impl ACOSolver {
         self.update_aco();
         while self.get_best_ant_total_violations().len() > 0 {
             self.update_aco();
-            self.cnt_super_not_change += 1;
         }
     }
 
@@ -79,15 +133,16 @@ impl ACOSolver {
 
     pub fn get_super_ant_score(&self) -> f64 {
         if let Some(ant) = &self.super_ant {
-            return ant.calc_all_path_length(self.colony.get_graph());
+            return ant.calc_all_path_length();
         }
         return 0.0;
     }
-    pub fn get_best_ant_score(&self) -> f64 {
-        if let Some(ant) = &self.best_ant {
-            return ant.calc_all_path_length(self.colony.get_graph());
+
+    pub fn get_super_ant_scores(&self) -> Score {
+        if let Some(ant) = &self.super_ant {
+            return ant.get_breakdown().score();
         }
-        return 0.0;
+        return Score::default();
     }
 
     fn update_aco(&mut self) {
@@ -95,12 +150,15 @@ impl ACOSolver {
         self.reset_aco();
         if let Some(best_ant) = &self.best_ant {
             if let Some(super_ant) = &self.super_ant {
-                if best_ant.calc_all_path_length(self.colony.get_graph())
-                    < super_ant.calc_all_path_length(self.colony.get_graph())
+                if best_ant
+                    .get_breakdown()
+                    .score()
+                    .is_better_than(&super_ant.get_breakdown().score())
                 {
                     self.super_ant = Some(best_ant.clone());
+                    self.cnt_super_not_change = 0;
                 } else {
-                    self.cnt_super_not_change;
+                    self.cnt_super_not_change += 1;
                 }
             } else {
                 self.super_ant = Some(best_ant.clone());
@@ -108,26 +166,103 @@ impl ACOSolver {
             /*
             println!(
                 "best path length: {}",
-                best_ant.calc_all_path_length(self.colony.get_graph())
+                best_ant.calc_all_path_length()
             );
             */
         }
+        self.iteration += 1;
+        self.entropy = self.colony.get_graph().pheromone_entropy();
+        if let Some(reason) = self.detect_stagnation() {
+            self.restart(reason);
+        }
+    }
+
+    fn detect_stagnation(&self) -> Option<StagnationReason> {
         if self.cnt_super_not_change > self.parameters.super_not_change {
-            println!("reset pheromone!");
-            self.colony.reset_pheromone();
-            self.cnt_super_not_change = 0;
+            return Some(StagnationReason::NoImprovement);
         }
+        let settings = &self.parameters.stagnation;
+        let in_cooldown = self
+            .last_restart
+            .is_some_and(|x| self.iteration < x + settings.cooldown);
+        if !in_cooldown && self.entropy < settings.min_entropy {
+            return Some(StagnationReason::LowEntropy);
+        }
+        None
+    }
+
+    //フェロモンを戻すか寄せるかして探索をやり直す。super_antは残す
+    fn restart(&mut self, reason: StagnationReason) {
+        let settings = &self.parameters.stagnation;
+        let restart = Restart {
+            iteration: self.iteration,
+            reason,
+            mode: settings.restart,
+            entropy: self.entropy,
+            iterations_without_improvement: self.cnt_super_not_change,
+        };
+        match settings.restart {
+            RestartMode::Reset => self.colony.reset_pheromone(),
+            RestartMode::Smooth => self.colony.smooth_pheromone(settings.smoothing_rate),
+        }
+        self.entropy = self.colony.get_graph().pheromone_entropy();
+        self.cnt_super_not_change = 0;
+        self.last_restart = Some(self.iteration);
+        self.restarts.push(restart);
     }
 
     fn update_colony(&mut self) {
-        self.colony.update_colony();
-        self.update_next_pheromone();
+        self.colony.update_colony(&mut self.rng);
         self.best_ant = Some(self.colony.get_best_ant());
+        if self.parameters.local_search.mode == LocalSearchMode::EachIteration {
+            self.local_search_best_ant();
+        }
+        self.calc_next_pheromone();
+        self.update_next_pheromone();
+    }
+
+    //best_antを局所探索で直す。EachIterationでは直した配置がこの世代のフェロモンに置かれる
+    pub fn local_search_best_ant(&mut self) -> Option<LocalSearchResult> {
+        let best_ant = self.best_ant.as_mut()?;
+        Some(best_ant.local_search(self.colony.get_graph()))
+    }
+
+    //実行の終わりに呼ぶ。EndOfRunなら最後のbest_antを直してsuper_antと比べる
+    pub fn finish_run(&mut self) {
+        if self.parameters.local_search.mode != LocalSearchMode::EndOfRun {
+            return;
+        }
+        if let Some(result) = self.local_search_best_ant() {
+            if result.after.is_better_than(&self.get_super_ant_scores()) || self.super_ant.is_none()
+            {
+                self.super_ant = self.best_ant.clone();
+            }
+        }
     }
 
     fn reset_aco(&mut self) {
         self.colony.reset_aco();
     }
+    //super_antはこの世代の結果を含めた最良として渡す
+    fn calc_next_pheromone(&mut self) {
+        let iteration_best = match &self.best_ant {
+            Some(ant) => ant,
+            None => return,
+        };
+        let global_best = match &self.super_ant {
+            Some(super_ant)
+                if !iteration_best
+                    .get_breakdown()
+                    .score()
+                    .is_better_than(&super_ant.get_breakdown().score()) =>
+            {
+                super_ant
+            }
+            _ => iteration_best,
+        };
+        self.colony.calc_next_pheromone(iteration_best, global_best);
+    }
+
     fn update_next_pheromone(&mut self) {
         self.colony.update_next_pheromone();
     }
@@ -138,59 +273,23 @@ impl ACOSolver {
     pub fn get_best_ant(&self) -> Option<Ant> {
         return self.best_ant.clone();
     }
-    pub fn get_best_ant_same_group_violations(&self) -> Vec<Violations> {
-        if let Some(best_ant) = &self.best_ant {
-            return best_ant.get_same_students_group_violations();
-        }
-        return Vec::new();
-    }
-    pub fn get_best_ant_same_teacher_violations(&self) -> Vec<Violations> {
-        if let Some(best_ant) = &self.best_ant {
-            return best_ant.get_same_teacher_violations();
-        }
-        return Vec::new();
-    }
-    pub fn get_best_ant_capacity_violations(&self) -> Vec<Violations> {
+    pub fn get_best_ant_violations(&self, kind: ConstraintKind) -> Vec<Violations> {
         if let Some(best_ant) = &self.best_ant {
-            return best_ant.get_capacity_violations(self.colony.get_graph());
+            return best_ant.get_violations(self.colony.get_graph(), kind);
         }
         return Vec::new();
     }
 
     pub fn get_best_ant_total_violations(&self) -> Vec<Violations> {
         let mut res = Vec::new();
-        res.append(&mut self.get_best_ant_same_group_violations());
-        res.append(&mut self.get_best_ant_same_teacher_violations());
-        res.append(&mut self.get_best_ant_capacity_violations());
-        return res;
-    }
-
-    pub fn get_best_ant_same_teacher_violations_strictly(&self) -> Vec<Violations> {
-        if let Some(best_ant) = &self.best_ant {
-            return best_ant.get_same_teacher_violations_strictly(&self.input);
+        for constraint in CONSTRAINTS.iter() {
+            res.append(&mut self.get_best_ant_violations(constraint.kind()));
         }
-        return Vec::new();
+        res
     }
 
-    pub fn get_best_ant_same_group_violations_strictly(&self) -> Vec<Violations> {
-        if let Some(best_ant) = &self.best_ant {
-            return best_ant.get_same_students_group_violations_strictly(&self.input);
-        }
-        return Vec::new();
-    }
-
-    pub fn get_best_ant_strabble_days_violations(&self) -> Vec<Violations> {
-        if let Some(best_ant) = &self.best_ant {
-            return best_ant.get_strabble_days_violations(&self.input);
-        }
-        return Vec::new();
-    }
-    pub fn get_best_ant_total_violations_strictly(&self) -> Vec<Violations> {
-        let mut res = Vec::new();
-        res.append(&mut self.get_best_ant_same_group_violations());
-        res.append(&mut self.get_best_ant_same_teacher_violations_strictly());
-        res.append(&mut self.get_best_ant_capacity_violations());
-        res
+    pub fn constraint_context(&self) -> ConstraintContext<'_> {
+        self.colony.get_graph().constraint_context()
     }
 
     fn ceiling_max_pheromone(&self) -> f64 {
@@ -209,13 +308,103 @@ impl ACOSolver {
     }
 }
 
-pub struct ACOSolverManager {
-    pub solver: Mutex<Option<ACOSolver>>,
+impl Solver for ACOSolver {
+    fn kind(&self) -> SolverKind {
+        SolverKind::Aco
+    }
+
+    fn initialize(&mut self) {
+        self.colony.reset_pheromone();
+        self.colony.reset_aco();
+        self.best_ant = None;
+        self.super_ant = None;
+        self.cnt_super_not_change = 0;
+        self.iteration = 0;
+        self.entropy = 1.0;
+        self.restarts.clear();
+        self.last_restart = None;
+        self.rng = SolverRng::seed_from_u64(self.seed);
+    }
+
+    fn step(&mut self) {
+        self.update_aco();
+    }
+
+    fn best_solution(&self) -> Option<&Placements> {
+        self.super_ant.as_ref().map(|x| x.get_placements())
+    }
+
+    fn finish_run(&mut self) {
+        ACOSolver::finish_run(self);
+    }
+
+    fn get_locks(&self) -> &Vec<Option<(usize, usize)>> {
+        self.colony.get_graph().get_locks()
+    }
+
+    fn set_locks(&mut self, locks: &Vec<Option<(usize, usize)>>) {
+        self.colony.get_graph_as_mut().set_locks(locks);
+    }
+
+    fn get_parameters(&self) -> &AcoParameters {
+        &self.parameters
+    }
+
+    fn get_input(&self) -> &Input {
+        &self.input
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn constraint_context(&self) -> ConstraintContext<'_> {
+        ACOSolver::constraint_context(self)
+    }
+
+    //フェロモンから求めた、その場所が選ばれる確率
+    fn slot_preference(&self, class_index: usize, room: usize, period: usize) -> Option<f64> {
+        let ant = self.super_ant.as_ref()?;
+        let (rp_v, prov_v) =
+            ant.calc_prob_from_v_igunore_visited(class_index, self.colony.get_graph());
+        let mut prov = 0.0;
+        for (i, rp) in rp_v.iter().enumerate() {
+            if rp[0] == room && rp[1] == period {
+                prov = prov_v[i];
+            }
+        }
+        Some(prov)
+    }
+
+    fn as_aco_mut(&mut self) -> Option<&mut ACOSolver> {
+        Some(self)
+    }
+
+    fn breakdown(&self) -> ScoreBreakdown {
+        match &self.super_ant {
+            Some(ant) => *ant.get_breakdown(),
+            None => ScoreBreakdown::default(),
+        }
+    }
+
+    fn current_score(&self) -> Score {
+        self.best_ant
+            .as_ref()
+            .map_or(Score::default(), |ant| ant.get_breakdown().score())
+    }
+
+    fn diversity(&self) -> Option<f64> {
+        Some(self.entropy)
+    }
+
+    fn restarts(&self) -> &[Restart] {
+        &self.restarts
+    }
 }
 
 #[tauri::command]
 pub fn handle_one_hot_pheromone(
-    solver_manager: tauri::State<'_, ACOSolverManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
     class_id: usize,
     room_id: usize,
     period_id: usize,
@@ -226,20 +415,72 @@ pub fn handle_one_hot_pheromone(
     );
     let mut managed_solver = solver_manager.solver.lock().unwrap();
     if let Some(solver) = managed_solver.as_mut() {
+        let solver = solver.as_aco_mut().ok_or("solver is not ACO")?;
         solver.set_one_hot_pheromone(class_id, room_id, period_id);
     }
     Ok(())
 }
 
-#[tauri::command]
-pub fn handle_read_cells(
-    solver_manager: tauri::State<'_, ACOSolverManager>,
-    cells: Vec<Option<ActiveCell>>,
-) -> Result<(), String> {
-    let mut managed_solver = solver_manager.solver.lock().unwrap();
-    if let Some(solver) = managed_solver.as_mut() {
-        solver.colony.get_graph_as_mut().load_cells(&cells);
-        return Ok(());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::aco::aco_parameters::AcoSettings;
+    use crate::input::sample;
+
+    fn new_solver(seed: u64) -> ACOSolver {
+        let input = sample::input();
+        let settings = AcoSettings {
+            seed: Some(seed),
+            ..AcoSettings::default()
+        };
+        ACOSolver::new(AcoParameters::new(&settings, &input), input)
+    }
+
+    fn run(solver: &mut ACOSolver, steps: usize) -> Vec<Option<[usize; 2]>> {
+        solver.initialize();
+        for _ in 0..steps {
+            solver.step();
+        }
+        solver.best_solution().unwrap().get_slots().clone()
+    }
+
+    #[test]
+    fn same_seed_gives_same_placements() {
+        let first = run(&mut new_solver(42), 20);
+        let second = run(&mut new_solver(42), 20);
+        assert_eq!(first, second);
+    }
+
+    //Stopの後にもう一度Startしても、前の実行の乱数を引き継がない
+    #[test]
+    fn initialize_replays_the_seed() {
+        let mut solver = new_solver(7);
+        let first = run(&mut solver, 20);
+        let second = run(&mut solver, 20);
+        assert_eq!(first, second);
+        assert_eq!(solver.iteration, 20);
+    }
+
+    //エントロピーがずっと低くても、再出発の間はcooldown世代あける
+    #[test]
+    fn restarts_wait_for_cooldown() {
+        let input = sample::input();
+        let mut settings = AcoSettings {
+            seed: Some(3),
+            ..AcoSettings::default()
+        };
+        settings.stagnation.min_entropy = 1.0;
+        settings.stagnation.cooldown = 5;
+        let mut solver = ACOSolver::new(AcoParameters::new(&settings, &input), input);
+        run(&mut solver, 30);
+        let iterations = solver
+            .restarts
+            .iter()
+            .map(|x| x.iteration)
+            .collect::<Vec<_>>();
+        assert!(iterations.len() >= 2, "restarts: {:?}", iterations);
+        for pair in iterations.windows(2) {
+            assert!(pair[1] - pair[0] >= 5, "restarts: {:?}", iterations);
+        }
     }
-    return Err("solver is not initialized".to_string());
 }
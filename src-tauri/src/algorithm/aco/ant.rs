@@ -1,56 +1,57 @@
 use super::aco_parameters::AcoParameters;
-use super::graph::{self, Graph};
-use super::violations::{self, Violations};
-use crate::input::class::{self, Class};
-use crate::input::room::Room;
-
-use crate::input::teacher::Teacher;
-use crate::input::Input;
-use crate::table_editor::Teachers;
+use super::aco_solver::SolverRng;
+use super::graph::{Graph, Slot};
+use super::ordering::ClassOrder;
+use super::violations::Violations;
+use crate::algorithm::constraint::{self, ConstraintKind, Placements, ScoreBreakdown};
+use crate::algorithm::local_search::{self, LocalSearchResult};
+use crate::algorithm::solver;
 use rand::seq::SliceRandom;
-use std::collections::HashMap;
-use std::collections::{BTreeMap, BTreeSet};
-use std::vec;
+use rand::Rng;
+
+//construct_pathで見るグラフ。ACSでは1つ置くたびに局所更新するので書き換えられる方を渡す
+pub enum PathGraph<'a> {
+    Shared(&'a Graph),
+    Local(&'a mut Graph),
+}
 
-static CAP_COEF: f64 = 2.0;
-static TEACHER_COEF: f64 = 5.0;
-static STUDENT_COEF: f64 = 3.0;
-static ABSENT_DAYS_COEF: f64 = 3.0;
-static STRADDLE_DAYS_COEF: f64 = 1.0;
-static COLLECTION_COEF: f64 = 1.0;
-static SEQUENTIAL_FROM_START_COEF: f64 = 4.0;
+impl PathGraph<'_> {
+    fn get(&self) -> &Graph {
+        match self {
+            PathGraph::Shared(graph) => graph,
+            PathGraph::Local(graph) => graph,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Ant {
-    visited_classes: Vec<bool>,
-    visited_roomperiods: Vec<Vec<bool>>,
-    corresponding_crp: Vec<[usize; 2]>,
+    placements: Placements,
+    //placementsのコスト。allocate_classesで置くたびに足していく
+    breakdown: ScoreBreakdown,
     parameters: AcoParameters,
-    //teachers_times[teacher_id][period] = [room_id, room_id, ...]gg
-    work_periods_each_teachers: Vec<BTreeMap<usize, Vec<usize>>>,
-    //teachers_times[teacher_id][period] = [room_id, room_id, ...]
-    work_periods_each_students: Vec<BTreeMap<usize, Vec<usize>>>,
 }
 
 impl Ant {
     pub fn new(parameters: AcoParameters) -> Ant {
-        let visited_classes = vec![false; parameters.num_of_classes as usize];
-        let visited_roomperiods =
-            vec![vec![false; parameters.num_of_periods as usize]; parameters.num_of_rooms as usize];
-        let corresponding_crp = vec![[0, 0]; parameters.num_of_classes as usize];
-        let parameters = parameters;
-        let teachers_times = vec![BTreeMap::new(); parameters.num_of_teachers as usize];
-        let students_times = vec![BTreeMap::new(); parameters.num_of_students as usize];
+        let placements = Ant::new_placements(&parameters);
         return Ant {
-            visited_classes,
-            visited_roomperiods,
-            corresponding_crp,
+            placements,
+            breakdown: ScoreBreakdown::default(),
             parameters,
-            work_periods_each_teachers: teachers_times,
-            work_periods_each_students: students_times,
         };
     }
 
+    fn new_placements(parameters: &AcoParameters) -> Placements {
+        Placements::new(
+            parameters.num_of_classes,
+            parameters.num_of_rooms,
+            parameters.num_of_periods,
+            parameters.num_of_teachers,
+            parameters.num_of_students,
+        )
+    }
+
     fn allocate_classes(
         &mut self,
         class_index: usize,
@@ -58,231 +59,132 @@ impl Ant {
         period_index: usize,
         graph: &Graph,
     ) {
-        let serial_size = graph.get_class(class_index).serial_size;
-        self.corresponding_crp[class_index] = [room_index, period_index];
-        self.visited_classes[class_index] = true;
-        for i in 0..serial_size {
-            self.visited_roomperiods[room_index][period_index + i] = true;
-        }
-        //get teacher indexes in classes,then add time;
-        for teacher_index in graph
-            .get_class_ref(class_index)
-            .get_teacher_indexes()
-            .iter()
-        {
-            if let Some(times) = self
-                .work_periods_each_teachers
-                .get_mut(*teacher_index as usize)
-            {
-                for i in 0..serial_size {
-                    if let Some(time) = times.get_mut(&(period_index + i)) {
-                        time.push(room_index);
-                    } else {
-                        times.insert(period_index + i, vec![room_index]);
-                    }
-                }
-            }
-        }
-        //get student group indexes in classes,then add time;
-        for student_index in graph
-            .get_class_ref(class_index)
-            .get_students_group_indexes()
-            .iter()
-        {
-            if let Some(times) = self
-                .work_periods_each_students
-                .get_mut(*student_index as usize)
-            {
-                for i in 0..serial_size {
-                    if let Some(time) = times.get_mut(&(period_index + i)) {
-                        time.push(room_index);
-                    } else {
-                        times.insert(period_index + i, vec![room_index]);
-                    }
-                }
-            }
-        }
+        self.breakdown.add_placement(
+            &graph.constraint_context(),
+            &self.placements,
+            class_index,
+            room_index,
+            period_index,
+        );
+        self.placements
+            .place(graph.get_class_ref(class_index), room_index, period_index);
     }
 
-    pub fn construct_path(&mut self, graph: &Graph) {
-        let shuffled_array = Ant::get_shuffled_array(self.parameters.num_of_classes);
-        self.work_periods_each_teachers =
-            vec![BTreeMap::new(); self.parameters.num_of_teachers as usize];
-        self.work_periods_each_students =
-            vec![BTreeMap::new(); self.parameters.num_of_students as usize];
+    pub fn construct_path(&mut self, mut graph: PathGraph, rng: &mut SolverRng) {
+        let shuffled_array = Ant::get_shuffled_array(self.parameters.num_of_classes, rng);
+        self.reset_ant();
+        //全ての授業が置かれていない状態から、置くたびにコストを足していく
+        self.breakdown = constraint::breakdown(&graph.get().constraint_context(), &self.placements);
         //preallocate locked classes
         for v in shuffled_array.iter() {
-            if let Some(to) = graph.get_classes_is_locked(*v) {
-                self.allocate_classes(*v, to.0, to.1, graph);
+            if let Some(to) = graph.get().get_classes_is_locked(*v) {
+                self.allocate_classes(*v, to.0, to.1, graph.get());
             }
         }
         //allocate with pheromone
-        for v in shuffled_array.iter() {
-            if self.visited_classes[*v] {
+        let mut order = ClassOrder::new(
+            self.parameters.class_ordering,
+            graph.get(),
+            &self.placements,
+            shuffled_array,
+            rng,
+        );
+        while let Some(v) = order.next(&self.placements) {
+            let (to_vertex, to_period) = self.calc_prob_from_v(v, graph.get());
+            //空いている場所がなければ置かずに進む。置かなかった分はUnassignedのコストに残る
+            if to_vertex.is_empty() {
                 continue;
             }
-            let (to_vertex, to_period) = self.calc_prob_from_v(*v, graph);
             let to: [usize; 2];
-            if rand::random::<f64>() < self.parameters.ant_prob_random {
-                to = to_vertex[rand::random::<usize>() % to_vertex.len()];
+            if rng.gen::<f64>() < self.parameters.ant_prob_random {
+                to = to_vertex[rng.gen_range(0..to_vertex.len())];
+            } else if self.parameters.pheromone.is_acs()
+                && rng.gen::<f64>() < self.parameters.pheromone.acs_exploitation
+            {
+                //ACSでは一定の確率で最も選ばれやすい場所をそのまま選ぶ
+                let prob = |i: usize| to_period[i] - if i == 0 { 0.0 } else { to_period[i - 1] };
+                let best = (0..to_period.len())
+                    .max_by(|&a, &b| prob(a).total_cmp(&prob(b)))
+                    .unwrap();
+                to = to_vertex[best];
             } else {
-                let random_p = rand::random::<f64>();
-                to = to_vertex[to_period.iter().position(|&x| x > random_p).unwrap()];
+                let random_p = rng.gen::<f64>();
+                //丸め誤差で最後の累積確率が1に届かないことがある
+                let position = to_period.iter().position(|&x| x > random_p);
+                to = to_vertex[position.unwrap_or(to_vertex.len() - 1)];
+            }
+            order.before_place(graph.get(), &self.placements, v, to[0], to[1]);
+            self.allocate_classes(v, to[0], to[1], graph.get());
+            if let PathGraph::Local(graph) = &mut graph {
+                self.apply_local_pheromone(graph, v, to[0], to[1]);
             }
-            self.allocate_classes(*v, to[0], to[1], graph);
         }
     }
 
-    fn calc_all_path_length_each_frame(&self) -> Vec<Vec<f64>> {
-        let mut length =
-            vec![
-                vec![1.0; self.parameters.num_of_periods / self.parameters.size_of_frame];
-                self.parameters.num_of_rooms
-            ];
-        for r in 0..self.parameters.num_of_rooms as usize {
-            for f in 0..self.parameters.num_of_periods / self.parameters.size_of_frame {
-                let mut is_empty = true;
-                let mut count_in_frame = 0;
-                for p in f * self.parameters.size_of_frame..(f + 1) * self.parameters.size_of_frame
+    //各授業の今の場所のedge_lengthに応じて置く量。weightは戦略ごとの重み
+    //グラフは読むだけなので、複数の蟻で同時に求めてから順に足せる
+    pub fn calc_pheromone_deposits(
+        &self,
+        graph: &Graph,
+        weight: f64,
+    ) -> Vec<(usize, usize, usize, f64)> {
+        let q = self.parameters.q * weight;
+        let ctx = graph.constraint_context();
+        let mut deposits = Vec::new();
+        for class_index in 0..self.parameters.num_of_classes {
+            if let Some([room, period]) = self.placements.get(class_index) {
+                if let Some(length) =
+                    constraint::placed_edge_length(&ctx, &self.placements, class_index)
                 {
-                    if !self.visited_roomperiods[r][p] {
-                    } else {
-                        count_in_frame += 1;
-                        is_empty = false;
-                    }
-                }
-                if count_in_frame != self.parameters.size_of_frame {
-                    length[r][f] +=
-                        ((self.parameters.size_of_frame - count_in_frame) as f64) * COLLECTION_COEF;
-                }
-                if is_empty {
-                    length[r][f] = 1.0;
+                    deposits.push((class_index, room, period, q / length));
                 }
             }
         }
-        length
-    }
-
-    pub fn update_next_pheromone(&mut self, graph: &mut Graph) {
-        let length_period = self.calc_all_path_length_each_period(graph);
-        let length_room = self.calc_all_path_length_each_room(graph);
-        let length_frame = self.calc_all_path_length_each_frame();
-        for i in 0..self.corresponding_crp.len() {
-            let [room, period] = self.corresponding_crp[i];
-            let q = self.parameters.q;
-            graph.add_next_pheromone(
-                i,
-                room,
-                period,
-                q / (length_period[period]
-                    + length_room[room]
-                    + length_frame[room][period / self.parameters.size_of_frame]
-                    - 1.0
-                    - 1.0),
-            );
-        }
+        deposits
     }
 
-    // capacity ,students and teachers
-    fn calc_all_path_length_each_period(&self, graph: &Graph) -> Vec<f64> {
-        let mut length = vec![1.0; self.parameters.num_of_periods as usize];
-        for class_id in 0..self.corresponding_crp.len() {
-            let [room, period] = self.corresponding_crp[class_id];
-            if graph.get_room_ref(room).get_capacity()
-                < graph.get_class_ref(class_id).get_num_of_students()
-            {
-                length[period] += CAP_COEF;
-            }
-        }
-        for mp in self.work_periods_each_students.iter() {
-            for (period, v) in mp.iter() {
-                let ftime = (*v).len() as f64;
-                length[*period] += (ftime * (ftime - 1.0) / 2.0 as f64) * STUDENT_COEF;
-            }
-        }
-        for mp in self.work_periods_each_teachers.iter() {
-            for (period, v) in mp.iter() {
-                let ftime = (*v).len() as f64;
-                length[*period] += (ftime * (ftime - 1.0) / 2.0 as f64) * TEACHER_COEF;
-            }
+    pub fn update_next_pheromone(&self, graph: &mut Graph, weight: f64) {
+        for (class_index, room, period, pheromone) in self.calc_pheromone_deposits(graph, weight) {
+            graph.add_next_pheromone(class_index, room, period, pheromone);
         }
-        for class_id in 0..self.corresponding_crp.len() {
-            let [room, period] = self.corresponding_crp[class_id];
-            let absent_days = self.calc_absent_days(
-                &graph.get_class_ref(class_id).get_teacher_indexes(),
-                graph.get_teachers_ref(),
-            );
-            if (absent_days.contains(&period)) {
-                length[period] += ABSENT_DAYS_COEF;
-            }
-        }
-        length
     }
 
-    // straddle days
-    fn calc_all_path_length_each_room(&self, graph: &Graph) -> Vec<f64> {
-        let mut length = vec![1.0; self.parameters.num_of_rooms as usize];
-        for class_id in 0..self.corresponding_crp.len() {
-            let [room, period] = self.corresponding_crp[class_id];
-            let serial_size = graph.get_class(class_id).serial_size;
-            if (period % self.parameters.num_of_day_lengths) + serial_size
-                > self.parameters.num_of_day_lengths
-            {
-                length[room] += STRADDLE_DAYS_COEF;
-            }
-        }
-        length
+    pub fn calc_all_path_length(&self) -> f64 {
+        self.breakdown.score().total()
     }
 
-    pub fn calc_all_path_length(&self, graph: &Graph) -> f64 {
-        let mut length = 1.0;
-        let length_period = self.calc_all_path_length_each_period(graph);
-        let length_room = self.calc_all_path_length_each_room(graph);
-        let length_frame = self.calc_all_path_length_each_frame();
-        for p in &length_period {
-            length += p - 1.0;
-        }
-        for r in &length_room {
-            length += r - 1.0;
-        }
-        for p in &length_frame {
-            for f in p {
-                length += f - 1.0;
-            }
-        }
-
-        length
+    //置きながら足したコストなので、配置全体は数え直さない
+    pub fn get_breakdown(&self) -> &ScoreBreakdown {
+        &self.breakdown
     }
 
-    fn calc_allocatable_room_periods(&self, serial_size: usize, graph: &Graph) -> Vec<[usize; 2]> {
-        let mut res = Vec::new();
-        for room in 0..self.parameters.num_of_rooms as usize {
-            for period in 0..(self.parameters.num_of_periods - serial_size + 1) as usize {
-                let mut is_allocatable = true;
-                for i in 0..serial_size {
-                    if self.visited_roomperiods[room][period + i] == true {
-                        is_allocatable = false;
-                        break;
-                    }
-                }
-                if is_allocatable {
-                    res.push([room, period]);
-                }
-            }
-        }
-        res
+    //固定された授業以外を局所探索で直す
+    pub fn local_search(&mut self, graph: &Graph) -> LocalSearchResult {
+        let locked = solver::locked_classes(graph.get_locks());
+        let ctx = graph.constraint_context();
+        local_search::hill_climb(
+            &ctx,
+            &mut self.placements,
+            &mut self.breakdown,
+            &locked,
+            self.parameters.local_search.max_steps,
+        )
     }
 
-    fn calc_absent_days(
-        &self,
-        teacher_indices: &Vec<usize>,
-        teachers: &Vec<Teacher>,
-    ) -> BTreeSet<usize> {
-        let mut res = BTreeSet::new();
-        for teacher_id in teacher_indices.iter() {
-            res.extend(teachers[*teacher_id].absent_days.clone());
+    //空いている場所。不在や日またぎのない場所が空いていればそれだけを返す
+    fn calc_allocatable_slots<'a>(&self, class_index: usize, graph: &'a Graph) -> Vec<&'a Slot> {
+        let serial_size = graph.get_class(class_index).serial_size;
+        let class_slots = graph.get_class_slots(class_index);
+        let is_free = |slot: &&Slot| self.placements.is_free(slot.room, slot.period, serial_size);
+        let res = class_slots
+            .valid()
+            .iter()
+            .filter(is_free)
+            .collect::<Vec<_>>();
+        if !res.is_empty() {
+            return res;
         }
-        res
+        class_slots.all().iter().filter(is_free).collect()
     }
 
     fn calc_prob_from_v(&self, v: usize, graph: &Graph) -> (Vec<[usize; 2]>, Vec<f64>) {
@@ -291,24 +193,28 @@ impl Ant {
         let mut pre_normalized_values = Vec::new();
         let alpha = self.parameters.alpha;
         let beta = self.parameters.beta;
-        let class_period_length = graph.get_class(v).serial_size;
+        let ctx = graph.constraint_context();
 
-        for [room, period] in self.calc_allocatable_room_periods(class_period_length, graph) {
+        //入力だけで決まる部分は前もって求めてあるので、他の授業との関係だけを測る
+        let candidates = self
+            .calc_allocatable_slots(v, graph)
+            .into_iter()
+            .map(|slot| {
+                let delta = slot.static_score
+                    + constraint::dynamic_delta(&ctx, &self.placements, v, slot.room, slot.period);
+                ([slot.room, slot.period], delta)
+            })
+            .collect::<Vec<_>>();
+        //hard制約を破らない場所が残っていれば、破る場所は選ばない(タブー)
+        let has_feasible = candidates.iter().any(|(_, delta)| delta.is_feasible());
+        for ([room, period], delta) in candidates {
+            if has_feasible && !delta.is_feasible() {
+                continue;
+            }
             let pheromone = graph.get_pheromone(v, room, period);
 
-            let heuristics = self.parameters.q
-                / self.calc_edge_length(
-                    graph.get_room_ref(room),
-                    graph.get_class_ref(v),
-                    &self.calc_absent_days(
-                        &graph.get_class_ref(v).get_teacher_indexes(),
-                        graph.get_teachers_ref(),
-                    ),
-                    period as usize,
-                    self.parameters.size_of_frame,
-                );
+            let heuristics = self.parameters.q / delta.total();
             let pre_normalized_value = pheromone.powf(alpha) * heuristics.powf(beta);
-            if v == 0 {}
             sum_value += pre_normalized_value;
             to_vertexes.push([room, period]);
             pre_normalized_values.push(pre_normalized_value);
@@ -333,27 +239,20 @@ impl Ant {
         let mut to_pheromones = Vec::new();
         let alpha = self.parameters.alpha;
         let beta = self.parameters.beta;
+        let ctx = graph.constraint_context();
+        //deltaは自分の配置を数えないので、外さずに測れる
+        let placements = &self.placements;
 
-        for room in 0..self.parameters.num_of_rooms as usize {
-            for period in 0..self.parameters.num_of_periods as usize {
-                let pre_pheromone = graph.get_pheromone(v, room, period);
-                let heuristics = self.parameters.q
-                    / self.calc_edge_length(
-                        graph.get_room_ref(room),
-                        graph.get_class_ref(v),
-                        &self.calc_absent_days(
-                            &graph.get_class_ref(v).get_teacher_indexes(),
-                            graph.get_teachers_ref(),
-                        ),
-                        period as usize,
-                        self.parameters.size_of_frame,
-                    );
-                let pheromone = pre_pheromone.powf(alpha) * heuristics.powf(beta);
-                if v == 0 {}
-                sum_pheromone += pheromone;
-                to_vertexes.push([room, period]);
-                to_pheromones.push(pheromone);
-            }
+        for slot in graph.get_class_slots(v).all() {
+            let (room, period) = (slot.room, slot.period);
+            let pre_pheromone = graph.get_pheromone(v, room, period);
+            let delta =
+                slot.static_score + constraint::dynamic_delta(&ctx, placements, v, room, period);
+            let heuristics = self.parameters.q / delta.total();
+            let pheromone = pre_pheromone.powf(alpha) * heuristics.powf(beta);
+            sum_pheromone += pheromone;
+            to_vertexes.push([room, period]);
+            to_pheromones.push(pheromone);
         }
         let to_prob = to_pheromones
             .iter()
@@ -362,233 +261,44 @@ impl Ant {
         (to_vertexes, to_prob)
     }
 
-    fn calc_edge_length(
+    //ACSの局所更新。今置いた場所のフェロモンを初期値へ近づけ、後の蟻が別の場所を選びやすくする
+    fn apply_local_pheromone(
         &self,
-        room: &Room,
-        class: &Class,
-        absent_days: &BTreeSet<usize>,
+        graph: &mut Graph,
+        class_index: usize,
+        room: usize,
         period: usize,
-        size_of_frame: usize,
-    ) -> f64 {
-        let mut edge_length = 1.0;
-        //capacity violation
-        if class.get_num_of_students() > room.get_capacity() {
-            edge_length += CAP_COEF;
-        }
-        //students violation
-        for id in class.get_students_group_indexes().iter() {
-            if let Some(times) = self.work_periods_each_students.get(*id as usize) {
-                if let Some(time) = times.get(&(period as usize)) {
-                    let ftime = (*time).len() as f64;
-                    edge_length += (ftime * (ftime - 1.0) / 2.0 as f64) * STUDENT_COEF;
-                }
-            }
-        }
-        //teachers violation
-        for id in class.get_teacher_indexes().iter() {
-            if let Some(times) = self.work_periods_each_teachers.get(*id as usize) {
-                if let Some(time) = times.get(&(period as usize)) {
-                    let ftime = (*time).len() as f64;
-                    edge_length += (ftime * (ftime - 1.0) / 2.0 as f64) * TEACHER_COEF;
-                }
-            }
-        }
-        //teacher abset days violation
-        if absent_days.contains(&period) {
-            edge_length += ABSENT_DAYS_COEF;
-        }
-        //straddle days violation
-        if (period % self.parameters.num_of_day_lengths) + class.serial_size
-            > self.parameters.num_of_day_lengths
-        {
-            edge_length += STRADDLE_DAYS_COEF;
-        }
-        //collection violation
-        {
-            let start_frame = period / size_of_frame * size_of_frame;
-            let mut count_in_frame = 0;
-            for p in start_frame..(start_frame + size_of_frame) {
-                if self.visited_roomperiods[room.index][p] {
-                    count_in_frame += 1;
-                }
-            }
-            edge_length += (size_of_frame - count_in_frame) as f64 * COLLECTION_COEF;
-            if count_in_frame == 0 {
-                edge_length += (size_of_frame) as f64 * COLLECTION_COEF;
-            }
-        }
-        edge_length
+    ) {
+        let rate = self.parameters.pheromone.acs_local_rate;
+        let tau0 = self.parameters.q;
+        let pheromone = graph.get_pheromone(class_index, room, period);
+        graph.set_pheromone(
+            class_index,
+            room,
+            period,
+            (1.0 - rate) * pheromone + rate * tau0,
+        );
     }
 
-    fn get_shuffled_array(num_of_classes: usize) -> Vec<usize> {
+    fn get_shuffled_array(num_of_classes: usize, rng: &mut SolverRng) -> Vec<usize> {
         let mut array = Vec::new();
         for i in 0..num_of_classes as usize {
             array.push(i);
         }
-        let mut rng = rand::thread_rng();
-        array.shuffle(&mut rng);
+        array.shuffle(rng);
         array
     }
 
     pub fn reset_ant(&mut self) {
-        self.visited_classes = vec![false; self.parameters.num_of_classes as usize];
-        self.visited_roomperiods = vec![
-            vec![false; self.parameters.num_of_periods as usize];
-            self.parameters.num_of_rooms as usize
-        ];
-        self.corresponding_crp = vec![[0, 0]; self.parameters.num_of_classes as usize];
+        self.placements = Ant::new_placements(&self.parameters);
+        self.breakdown = ScoreBreakdown::default();
     }
 
-    pub fn get_corresponding_crp(&self) -> &Vec<[usize; 2]> {
-        &self.corresponding_crp
+    pub fn get_placements(&self) -> &Placements {
+        &self.placements
     }
 
-    pub fn get_same_teacher_violations(&self) -> Vec<Violations> {
-        let mut res = Vec::new();
-        for (_, mp) in (&self.work_periods_each_teachers).iter().enumerate() {
-            for (period_id, time) in mp {
-                if time.len() > 1 {
-                    let violations = Violations::new(*period_id as usize, time.clone());
-                    res.push(violations);
-                }
-            }
-        }
-        res
-    }
-    pub fn get_same_students_group_violations(&self) -> Vec<Violations> {
-        let mut res = Vec::new();
-        for (_, mp) in (&self.work_periods_each_students).iter().enumerate() {
-            for (period_id, time) in mp {
-                if time.len() > 1 {
-                    let violations = Violations::new(*period_id as usize, time.clone());
-                    res.push(violations);
-                }
-            }
-        }
-        res
-    }
-
-    pub fn get_capacity_violations(&self, graph: &Graph) -> Vec<Violations> {
-        let mut res = Vec::new();
-        for class_id in 0..self.corresponding_crp.len() {
-            let [room, period] = self.corresponding_crp[class_id];
-            if graph.get_room_ref(room).get_capacity()
-                < graph.get_class_ref(class_id).get_num_of_students()
-            {
-                let mut v = Vec::new();
-                v.push(class_id);
-                let violations = Violations::new(period as usize, v);
-                res.push(violations);
-            }
-        }
-        res
-    }
-
-    pub fn get_same_teacher_violations_strictly(&self, input: &Input) -> Vec<Violations> {
-        let mut res = Vec::new();
-        let period = self.parameters.num_of_periods;
-        let room = self.parameters.num_of_rooms;
-        let mut table: Vec<Vec<Vec<usize>>> = Vec::with_capacity(period);
-        for _ in 0..period {
-            let mut period_vec = Vec::with_capacity(room);
-            for _ in 0..room {
-                period_vec.push(Vec::new());
-            }
-            table.push(period_vec);
-        }
-        let classes = input.get_classes();
-        for (class_id, [room, period]) in self.corresponding_crp.iter().enumerate() {
-            let class_size = classes[class_id].serial_size;
-            for i in 0..class_size {
-                //ここに教師IDを入れる
-                table[period + i][*room].extend(classes[class_id].teacher_indexes.clone());
-            }
-        }
-
-        let mut same_teacher: Vec<Vec<Vec<usize>>> =
-            Vec::with_capacity(self.parameters.num_of_periods);
-        for i in 0..self.parameters.num_of_periods {
-            same_teacher.push(vec![vec![]; self.parameters.num_of_teachers]);
-        }
-        for room_id in 0..room {
-            for period in 0..period {
-                for teacher_id in table[period][room_id].iter() {
-                    same_teacher[period][*teacher_id].push(room_id);
-                }
-            }
-        }
-        for (i, vv) in same_teacher.iter().enumerate() {
-            for v in vv {
-                if v.len() > 1 {
-                    let mut v = v.clone();
-                    v.sort();
-                    let violations = Violations::new(i, v);
-                    res.push(violations);
-                }
-            }
-        }
-        res
-    }
-
-    pub fn get_same_students_group_violations_strictly(&self, input: &Input) -> Vec<Violations> {
-        let mut res = Vec::new();
-        let period = self.parameters.num_of_periods;
-        let room = self.parameters.num_of_rooms;
-        let mut table: Vec<Vec<Vec<usize>>> = Vec::with_capacity(period);
-        for _ in 0..period {
-            let mut period_vec = Vec::with_capacity(room);
-            for _ in 0..room {
-                period_vec.push(Vec::new());
-            }
-            table.push(period_vec);
-        }
-        let classes: &Vec<Class> = input.get_classes();
-        for (class_id, [room, period]) in self.corresponding_crp.iter().enumerate() {
-            let class_size = classes[class_id].serial_size;
-            for i in 0..class_size {
-                //ここに学生IDをいれる
-                table[period + i][*room].extend(classes[class_id].students_group_indexes.clone());
-            }
-        }
-        let mut same_group: Vec<Vec<Vec<usize>>> =
-            Vec::with_capacity(self.parameters.num_of_periods);
-        for i in 0..self.parameters.num_of_periods {
-            same_group.push(vec![vec![]; self.parameters.num_of_students]);
-        }
-        for room_id in 0..room {
-            for period in 0..period {
-                for student_id in table[period][room_id].iter() {
-                    same_group[period][*student_id].push(room_id);
-                }
-            }
-        }
-        for (i, vv) in same_group.iter().enumerate() {
-            for v in vv {
-                if v.len() > 1 {
-                    let mut v = v.clone();
-                    v.sort();
-                    let violations = Violations::new(i, v);
-                    res.push(violations);
-                }
-            }
-        }
-        res
-    }
-
-    pub fn get_strabble_days_violations(&self, input: &Input) -> Vec<Violations> {
-        let mut res = Vec::new();
-        let period = self.parameters.num_of_periods;
-        for (class_id, [room_id, _]) in self.corresponding_crp.iter().enumerate() {
-            let size = input.get_classes()[class_id].serial_size;
-            let mut period = period % self.parameters.num_of_day_lengths;
-            period += size;
-            if period > self.parameters.num_of_day_lengths {
-                let mut v = Vec::new();
-                v.push(room_id.clone());
-                let violations = Violations::new(period, v);
-                res.push(violations);
-            }
-        }
-        res
+    pub fn get_violations(&self, graph: &Graph, kind: ConstraintKind) -> Vec<Violations> {
+        constraint::violations_of_kind(&graph.constraint_context(), &self.placements, kind)
     }
 }
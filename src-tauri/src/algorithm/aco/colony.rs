@@ -1,12 +1,20 @@
 use super::aco_parameters::AcoParameters;
-use super::ant::Ant;
+use super::aco_solver::SolverRng;
+use super::ant::{Ant, PathGraph};
 use super::graph::Graph;
+use super::pheromone::PheromoneStrategy;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct Colony {
     parameters: AcoParameters,
     graph: Graph,
     ants: Vec<Ant>,
+    //蟻を並列に作るスレッド。毎世代作り直さないように持っておく。1スレッドならNone
+    pool: Option<Arc<ThreadPool>>,
 }
 
 impl Colony {
@@ -15,26 +23,30 @@ impl Colony {
         for _ in 0..parameters.num_of_ants {
             ants.push(Ant::new(parameters.clone()));
         }
+        let pool = new_pool(parameters.num_of_threads);
         return Colony {
             parameters,
             graph,
             ants,
+            pool,
         };
     }
 
     pub fn get_best_ant(&mut self) -> Ant {
         let mut best_ant = &self.ants[0];
+        let mut best_score = best_ant.get_breakdown().score();
         for ant in self.ants.iter() {
-            if ant.calc_all_path_length(&self.graph) < best_ant.calc_all_path_length(&self.graph) {
+            let score = ant.get_breakdown().score();
+            if score.is_better_than(&best_score) {
                 best_ant = ant;
+                best_score = score;
             }
         }
         return best_ant.clone();
     }
 
-    pub fn update_colony(&mut self) {
-        self.construct_ants();
-        self.calc_next_pheromone();
+    pub fn update_colony(&mut self, rng: &mut SolverRng) {
+        self.construct_ants(rng);
     }
 
     pub fn reset_aco(&mut self) {
@@ -42,14 +54,89 @@ impl Colony {
         self.reset_colony();
     }
 
-    fn construct_ants(&mut self) {
-        for ant in self.ants.iter_mut() {
-            ant.construct_path(&self.graph);
+    //蟻ごとにソルバーの乱数からシードを引いて、別々の乱数で作る。
+    //スレッド数が変わっても同じシードなら同じ結果になる
+    fn construct_ants(&mut self, rng: &mut SolverRng) {
+        let seeds = self.ants.iter().map(|_| rng.gen()).collect::<Vec<u64>>();
+        //ACSでは1匹作るごとに局所更新し、次の蟻はそれを見て作るので順に作る
+        if self.parameters.pheromone.is_acs() {
+            for (ant, seed) in self.ants.iter_mut().zip(seeds) {
+                ant.construct_path(
+                    PathGraph::Local(&mut self.graph),
+                    &mut SolverRng::seed_from_u64(seed),
+                );
+            }
+            return;
+        }
+        let graph = &self.graph;
+        let construct = |(ant, seed): (&mut Ant, u64)| {
+            ant.construct_path(
+                PathGraph::Shared(graph),
+                &mut SolverRng::seed_from_u64(seed),
+            );
+        };
+        match &self.pool {
+            Some(pool) => pool.install(|| self.ants.par_iter_mut().zip(seeds).for_each(construct)),
+            None => self.ants.iter_mut().zip(seeds).for_each(construct),
         }
     }
-    fn calc_next_pheromone(&mut self) {
-        for ant in self.ants.iter_mut() {
-            ant.update_next_pheromone(&mut self.graph);
+
+    //全ての蟻の量を並列に求めて、蟻の順に足す
+    fn deposit_all_ants(&mut self, weight: f64) {
+        let graph = &self.graph;
+        let ants = &self.ants;
+        let calc = |ant: &Ant| ant.calc_pheromone_deposits(graph, weight);
+        let deposits = match &self.pool {
+            Some(pool) => pool.install(|| ants.par_iter().map(calc).collect::<Vec<_>>()),
+            None => ants.iter().map(calc).collect::<Vec<_>>(),
+        };
+        for (class_index, room, period, pheromone) in deposits.into_iter().flatten() {
+            self.graph
+                .add_next_pheromone(class_index, room, period, pheromone);
+        }
+    }
+
+    //戦略に応じてnext_pheromoneに置く。global_bestはこれまでの最良の蟻
+    pub fn calc_next_pheromone(&mut self, iteration_best: &Ant, global_best: &Ant) {
+        let settings = self.parameters.pheromone.clone();
+        match settings.strategy {
+            PheromoneStrategy::AntSystem => {
+                self.deposit_all_ants(1.0);
+            }
+            PheromoneStrategy::IterationBest => {
+                iteration_best.update_next_pheromone(&mut self.graph, 1.0);
+            }
+            PheromoneStrategy::Elitist => {
+                self.deposit_all_ants(1.0);
+                global_best.update_next_pheromone(&mut self.graph, settings.elitist_weight);
+            }
+            PheromoneStrategy::RankBased => {
+                //上位rank_size - 1匹が(rank_size - 順位)倍、super_antがrank_size倍置く
+                let rank_size = settings.rank_size;
+                let mut ranked = self
+                    .ants
+                    .iter()
+                    .map(|ant| (ant.get_breakdown().score(), ant))
+                    .collect::<Vec<_>>();
+                ranked.sort_by(|a, b| {
+                    a.0.hard
+                        .total_cmp(&b.0.hard)
+                        .then(a.0.soft.total_cmp(&b.0.soft))
+                });
+                for (rank, (_, ant)) in ranked.iter().take(rank_size - 1).enumerate() {
+                    ant.update_next_pheromone(&mut self.graph, (rank_size - 1 - rank) as f64);
+                }
+                global_best.update_next_pheromone(&mut self.graph, rank_size as f64);
+            }
+            PheromoneStrategy::AntColonySystem => {
+                //大域更新はsuper_antの通った場所だけを蒸発させて置く
+                for (class_index, room, period, pheromone) in
+                    global_best.calc_pheromone_deposits(&self.graph, 1.0)
+                {
+                    self.graph
+                        .update_best_edge(class_index, room, period, pheromone);
+                }
+            }
         }
     }
     pub fn reset_colony(&mut self) {
@@ -62,38 +149,16 @@ impl Colony {
         self.graph.reset_graph_when_stagnation();
     }
 
+    pub fn smooth_pheromone(&mut self, rate: f64) {
+        self.graph.smooth_pheromone(rate);
+    }
+
+    //ACSはcalc_next_pheromoneで最良の蟻の場所だけを更新済みなので、全体は蒸発させない
     pub fn update_next_pheromone(&mut self) {
-        let num_of_classes = self.parameters.num_of_classes;
-        let num_of_rooms = self.parameters.num_of_rooms;
-        let num_of_periods = self.parameters.num_of_periods;
-        let rou = self.parameters.rou;
-        let tau_min = self.parameters.tau_min;
-        let tau_max = self.parameters.tau_max;
-        for i in 0..num_of_classes as usize {
-            for j in 0..num_of_rooms as usize {
-                for k in 0..num_of_periods as usize {
-                    self.graph.set_pheromone(
-                        i,
-                        j,
-                        k,
-                        self.graph.get_pheromone(i, j, k) * rou
-                            + self.graph.get_next_pheromone(i, j, k),
-                    );
-                    self.graph.set_pheromone(
-                        i,
-                        j,
-                        k,
-                        if self.graph.get_pheromone(i, j, k) < tau_min {
-                            tau_min
-                        } else if self.graph.get_pheromone(i, j, k) > tau_max {
-                            tau_max
-                        } else {
-                            self.graph.get_pheromone(i, j, k)
-                        },
-                    );
-                }
-            }
+        if self.parameters.pheromone.is_acs() {
+            return;
         }
+        self.graph.evaporate();
     }
 
     pub fn get_graph(&self) -> &Graph {
@@ -120,3 +185,113 @@ impl Colony {
         );
     }
 }
+
+//0ならrayonに任せて使えるだけのスレッドを使う
+fn new_pool(num_of_threads: usize) -> Option<Arc<ThreadPool>> {
+    if num_of_threads == 1 {
+        return None;
+    }
+    match ThreadPoolBuilder::new().num_threads(num_of_threads).build() {
+        Ok(pool) => Some(Arc::new(pool)),
+        Err(e) => {
+            println!("failed to build thread pool: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::aco::aco_parameters::AcoSettings;
+    use crate::input::sample;
+
+    const EPSILON: f64 = 1e-9;
+
+    fn new_colony(strategy: PheromoneStrategy) -> Colony {
+        let input = sample::input();
+        let mut settings = AcoSettings::default();
+        settings.pheromone.strategy = strategy;
+        settings.num_of_threads = 1;
+        let parameters = AcoParameters::new(&settings, &input);
+        let graph = Graph::new(
+            parameters.clone(),
+            input.get_classes().clone(),
+            input.get_rooms().clone(),
+            input.get_teachers().clone(),
+            input.get_calendar().clone(),
+        );
+        Colony::new(graph, parameters)
+    }
+
+    //1世代分の蟻を作り、最良の蟻で更新する前後のフェロモンと、最良の蟻が置く量を返す
+    fn update_once(colony: &mut Colony) -> (Vec<f64>, Vec<f64>, Vec<(usize, usize, usize, f64)>) {
+        colony.update_colony(&mut SolverRng::seed_from_u64(5));
+        let best = colony.get_best_ant();
+        let deposits = best.calc_pheromone_deposits(colony.get_graph(), 1.0);
+        let before = all_pheromones(colony.get_graph());
+        colony.calc_next_pheromone(&best, &best);
+        colony.update_next_pheromone();
+        (before, all_pheromones(colony.get_graph()), deposits)
+    }
+
+    fn slots(graph: &Graph) -> Vec<(usize, usize, usize)> {
+        let num_of_classes = graph.constraint_context().classes.len();
+        let mut res = (0..num_of_classes)
+            .flat_map(|c| {
+                graph
+                    .get_class_slots(c)
+                    .all()
+                    .iter()
+                    .map(move |x| (c, x.room, x.period))
+            })
+            .collect::<Vec<_>>();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    fn all_pheromones(graph: &Graph) -> Vec<f64> {
+        slots(graph)
+            .into_iter()
+            .map(|(c, room, period)| graph.get_pheromone(c, room, period))
+            .collect()
+    }
+
+    fn deposit_of(deposits: &[(usize, usize, usize, f64)], slot: (usize, usize, usize)) -> f64 {
+        deposits
+            .iter()
+            .filter(|x| (x.0, x.1, x.2) == slot)
+            .map(|x| x.3)
+            .sum()
+    }
+
+    //最良の蟻だけが置き、全体を蒸発させる
+    #[test]
+    fn iteration_best_evaporates_and_deposits_best() {
+        let mut colony = new_colony(PheromoneStrategy::IterationBest);
+        let (before, after, deposits) = update_once(&mut colony);
+        let parameters = colony.parameters.clone();
+        for (i, slot) in slots(colony.get_graph()).into_iter().enumerate() {
+            let expected = (before[i] * parameters.rou + deposit_of(&deposits, slot))
+                .max(parameters.tau_min)
+                .min(parameters.tau_max);
+            assert!((after[i] - expected).abs() < EPSILON, "{:?}", slot);
+        }
+    }
+
+    //ACSの大域更新は最良の蟻が置いた場所だけを変え、他は蒸発させない
+    #[test]
+    fn acs_updates_only_best_slots() {
+        let mut colony = new_colony(PheromoneStrategy::AntColonySystem);
+        let (before, after, deposits) = update_once(&mut colony);
+        let rou = colony.parameters.rou;
+        for (i, slot) in slots(colony.get_graph()).into_iter().enumerate() {
+            let expected = match deposits.iter().find(|x| (x.0, x.1, x.2) == slot) {
+                Some(x) => before[i] * rou + (1.0 - rou) * x.3,
+                None => before[i],
+            };
+            assert!((after[i] - expected).abs() < EPSILON, "{:?}", slot);
+        }
+    }
+}
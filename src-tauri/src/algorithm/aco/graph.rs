@@ -1,30 +1,58 @@
 use super::super::time_table::cell::Cell;
 use super::aco_parameters::AcoParameters;
-use crate::algorithm::time_table::cell::ActiveCell;
+use super::pheromone_table::PheromoneTable;
+use crate::algorithm::constraint::{self, ConstraintContext, Score};
+use crate::input::calendar::Calendar;
 use crate::input::class::{self, Class};
 use crate::input::room::Room;
 use crate::input::teacher::{self, Teacher};
 
+//授業を置ける場所と、他の授業によらない規則の増分
 #[derive(Clone)]
-pub struct Edge {
-    to: [usize; 2],
-    length: f64,
-    pheromone: f64,
-    heuristic: f64,
-    next_pheromone: f64,
+pub struct Slot {
+    pub room: usize,
+    pub period: usize,
+    pub static_score: Score,
+}
+
+//候補の教室と開始できる時限。不在や日またぎのない場所(valid)を先に並べる
+#[derive(Clone)]
+pub struct ClassSlots {
+    slots: Vec<Slot>,
+    num_of_valid: usize,
+    //置きにくさ。長く、関わる人が多く、置ける場所が少ないほど大きい
+    pub difficulty: f64,
+}
+
+impl ClassSlots {
+    pub fn all(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    pub fn valid(&self) -> &[Slot] {
+        &self.slots[..self.num_of_valid]
+    }
+
+    //置ける場所の数を数えるときに使う。validがなければ全て
+    pub fn feasible(&self) -> &[Slot] {
+        if self.num_of_valid == 0 {
+            return self.all();
+        }
+        self.valid()
+    }
 }
 
 #[derive(Clone)]
 pub struct Graph {
-    edges: Vec<Vec<Vec<Edge>>>,
+    pheromones: PheromoneTable,
+    class_slots: Vec<ClassSlots>,
     classes_is_locked: Vec<Option<(usize, usize)>>,
     num_of_classes: usize,
-    num_of_rooms: usize,
-    num_of_periods: usize,
     parameters: AcoParameters,
     classes: Vec<Class>,
     rooms: Vec<Room>,
     teachers: Vec<Teacher>,
+    calendar: Calendar,
 }
 
 impl Graph {
@@ -33,82 +61,75 @@ impl Graph {
         classes: Vec<Class>,
         rooms: Vec<Room>,
         teachers: Vec<Teacher>,
+        calendar: Calendar,
     ) -> Graph {
         let num_of_classes = parameters.num_of_classes;
-        let num_of_rooms = parameters.num_of_rooms;
-        let num_of_periods = parameters.num_of_periods;
-        let edges = vec![
-            vec![
-                vec![
-                    Edge {
-                        to: [0, 0],
-                        length: 0.0,
-                        pheromone: 0.0,
-                        heuristic: 0.0,
-                        next_pheromone: 0.0
-                    };
-                    num_of_periods as usize
-                ];
-                num_of_rooms as usize
-            ];
-            num_of_classes as usize
-        ];
+        let pheromones = PheromoneTable::new(
+            &classes,
+            parameters.num_of_rooms,
+            parameters.num_of_periods,
+            parameters.q,
+        );
         let classes_is_locked = vec![None; num_of_classes as usize];
         let mut res = Graph {
-            edges: edges,
+            pheromones,
+            class_slots: Vec::new(),
             classes_is_locked,
             num_of_classes: parameters.num_of_classes,
-            num_of_rooms: parameters.num_of_rooms,
-            num_of_periods: parameters.num_of_periods,
             parameters,
             classes,
             rooms,
             teachers,
+            calendar,
         };
-        res.prepare_graph();
-        return res;
-    }
-    pub fn get_class(&self, index: usize) -> &Class {
-        return &self.classes[index];
+        res.class_slots = res.prepare_class_slots();
+        res
     }
 
-    #[allow(dead_code)]
-    pub fn get_max_pheromone(&self) -> f64 {
-        let mut max_pheromone = 0.0;
-        for i in self.edges.iter() {
-            for j in i.iter() {
-                for k in j.iter() {
-                    if k.pheromone > max_pheromone {
-                        max_pheromone = k.pheromone;
+    //入力だけで決まる部分を一度だけ求める。空の配置で測れば他の授業によらない
+    fn prepare_class_slots(&self) -> Vec<ClassSlots> {
+        let ctx = self.constraint_context();
+        let placements = ctx.new_placements();
+        let num_of_periods = placements.num_of_periods();
+        let mut res = Vec::new();
+        for (class_index, class) in self.classes.iter().enumerate() {
+            let mut valid = Vec::new();
+            let mut invalid = Vec::new();
+            for &room in class.room_candidates_indexes.iter() {
+                for period in 0..(num_of_periods + 1).saturating_sub(class.serial_size) {
+                    let static_score =
+                        constraint::static_delta(&ctx, &placements, class_index, room, period);
+                    let slot = Slot {
+                        room,
+                        period,
+                        static_score,
+                    };
+                    if static_score.is_feasible() {
+                        valid.push(slot);
+                    } else {
+                        invalid.push(slot);
                     }
                 }
             }
+            let num_of_valid = valid.len();
+            let num_of_people = class.teacher_indexes.len() + class.students_group_indexes.len();
+            let difficulty =
+                (class.serial_size * (1 + num_of_people)) as f64 / num_of_valid.max(1) as f64;
+            valid.append(&mut invalid);
+            res.push(ClassSlots {
+                slots: valid,
+                num_of_valid,
+                difficulty,
+            });
         }
-        max_pheromone
+        res
     }
 
-    fn calc_edge_length(&self, p1: usize, p2: usize) -> f64 {
-        if p1 == p2 {
-            return 1 as f64;
-        }
-        if p1 > p2 {
-            return ((p1 - p2) + 1) as f64;
-        }
-        return ((p2 - p1) + 1) as f64;
+    pub fn get_class_slots(&self, class_index: usize) -> &ClassSlots {
+        &self.class_slots[class_index]
     }
-
-    fn prepare_graph(&mut self) {
-        for i in 0..self.num_of_classes as usize {
-            for j in 0..self.num_of_rooms as usize {
-                for k in 0..self.num_of_periods as usize {
-                    self.edges[i][j][k].to = [j as usize, k as usize];
-                    self.edges[i][j][k].length = self.calc_edge_length(i as usize, j as usize);
-                    self.edges[i][j][k].pheromone = self.parameters.q;
-                    self.edges[i][j][k].heuristic = self.parameters.q / self.edges[i][j][k].length;
-                    self.edges[i][j][k].next_pheromone = 0.0;
-                }
-            }
-        }
+    pub fn get_class(&self, index: usize) -> &Class {
+        return &self.classes[index];
     }
 
     pub fn get_classes_is_locked(&self, class_index: usize) -> Option<(usize, usize)> {
@@ -116,25 +137,79 @@ impl Graph {
     }
 
     pub fn reset_graph(&mut self) {
-        for i in 0..self.num_of_classes as usize {
-            for j in 0..self.num_of_rooms as usize {
-                for k in 0..self.num_of_periods as usize {
-                    self.edges[i][j][k].next_pheromone = 0.0;
-                }
-            }
-        }
+        self.pheromones.clear_next();
     }
     pub fn reset_graph_when_stagnation(&mut self) {
+        self.pheromones.fill(self.parameters.q);
+    }
+
+    //停滞からの再出発。初期値の方へrateだけ寄せる
+    pub fn smooth_pheromone(&mut self, rate: f64) {
+        self.pheromones.smooth(rate, self.parameters.q);
+    }
+
+    //蒸発させてnext_pheromoneを足し、[tau_min, tau_max]に収める
+    pub fn evaporate(&mut self) {
+        self.pheromones.evaporate(
+            self.parameters.rou,
+            self.parameters.tau_min,
+            self.parameters.tau_max,
+        );
+    }
+
+    //固定されていない授業ごとに、候補の場所のフェロモン分布のエントロピーを0から1にして平均する
+    pub fn pheromone_entropy(&self) -> f64 {
+        let mut sum_entropy = 0.0;
+        let mut num_of_classes = 0;
         for i in 0..self.num_of_classes as usize {
-            for j in 0..self.num_of_rooms as usize {
-                for k in 0..self.num_of_periods as usize {
-                    self.edges[i][j][k].pheromone = self.parameters.q;
-                }
+            if self.classes_is_locked[i].is_some() {
+                continue;
             }
+            let pheromones = self.pheromones.class_values(i);
+            if pheromones.len() < 2 {
+                continue;
+            }
+            let sum_pheromone = pheromones.iter().sum::<f64>();
+            if sum_pheromone <= 0.0 {
+                continue;
+            }
+            let entropy = pheromones
+                .iter()
+                .map(|x| x / sum_pheromone)
+                .filter(|&p| p > 0.0)
+                .map(|p| -p * p.ln())
+                .sum::<f64>();
+            sum_entropy += entropy / (pheromones.len() as f64).ln();
+            num_of_classes += 1;
+        }
+        if num_of_classes == 0 {
+            return 1.0;
         }
+        sum_entropy / num_of_classes as f64
     }
+
+    //ACSの大域更新。1つの場所だけをpheromone * rou + (1 - rou) * 置く量にする
+    pub fn update_best_edge(
+        &mut self,
+        class_index: usize,
+        room_index: usize,
+        period_index: usize,
+        pheromone: f64,
+    ) {
+        let rou = self.parameters.rou;
+        let next = self.pheromones.get(class_index, room_index, period_index) * rou
+            + (1.0 - rou) * pheromone;
+        self.pheromones.set(
+            class_index,
+            room_index,
+            period_index,
+            next.max(self.parameters.tau_min)
+                .min(self.parameters.tau_max),
+        );
+    }
+
     pub fn get_pheromone(&self, class_index: usize, room_index: usize, period_index: usize) -> f64 {
-        return self.edges[class_index][room_index][period_index].pheromone;
+        return self.pheromones.get(class_index, room_index, period_index);
     }
     pub fn get_class_ref(&self, class_index: usize) -> &Class {
         return &self.classes[class_index];
@@ -148,17 +223,21 @@ impl Graph {
     pub fn get_teachers_ref(&self) -> &Vec<Teacher> {
         return &self.teachers;
     }
-
-    #[allow(dead_code)]
-    pub fn add_pheromone(
-        &mut self,
-        class_index: usize,
-        room_index: usize,
-        period_index: usize,
-        pheromone: f64,
-    ) {
-        self.edges[class_index][room_index][period_index].next_pheromone += pheromone;
+    pub fn get_calendar_ref(&self) -> &Calendar {
+        return &self.calendar;
     }
+    pub fn constraint_context(&self) -> ConstraintContext<'_> {
+        ConstraintContext {
+            classes: &self.classes,
+            rooms: &self.rooms,
+            teachers: &self.teachers,
+            calendar: &self.calendar,
+            weights: &self.parameters.weights,
+            size_of_frame: self.parameters.size_of_frame,
+            num_of_students: self.parameters.num_of_students,
+        }
+    }
+
     pub fn add_next_pheromone(
         &mut self,
         class_index: usize,
@@ -166,7 +245,8 @@ impl Graph {
         period_index: usize,
         pheromone: f64,
     ) {
-        self.edges[class_index][room_index][period_index].next_pheromone += pheromone;
+        self.pheromones
+            .add_next(class_index, room_index, period_index, pheromone);
     }
     pub fn set_pheromone(
         &mut self,
@@ -175,23 +255,9 @@ impl Graph {
         period_index: usize,
         pheromone: f64,
     ) {
-        self.edges[class_index][room_index][period_index].pheromone = pheromone;
-    }
-    pub fn get_next_pheromone(
-        &self,
-        class_index: usize,
-        room_index: usize,
-        period_index: usize,
-    ) -> f64 {
-        return self.edges[class_index][room_index][period_index].next_pheromone;
-    }
-
-    #[allow(dead_code)]
-    pub fn get_edge(&self, class_index: usize, room_index: usize, period_index: usize) -> &Edge {
-        return &self.edges[class_index][room_index][period_index];
+        self.pheromones
+            .set(class_index, room_index, period_index, pheromone);
     }
-
-    #[allow(dead_code)]
     pub fn set_one_hot_pheromone(
         &mut self,
         class_index: usize,
@@ -200,29 +266,24 @@ impl Graph {
         min_pheromone: f64,
         max_pheromone: f64,
     ) {
-        for j in 0..self.num_of_rooms as usize {
-            for k in 0..self.num_of_periods as usize {
-                self.edges[class_index][j][k].pheromone = min_pheromone;
-            }
-        }
-        self.edges[class_index][room_index][period_index].pheromone =
-            self.parameters.q * max_pheromone;
+        self.pheromones
+            .class_values_mut(class_index)
+            .fill(min_pheromone);
+        self.pheromones.set(
+            class_index,
+            room_index,
+            period_index,
+            self.parameters.q * max_pheromone,
+        );
     }
 
-    pub fn load_cells(&mut self, cells: &Vec<Option<ActiveCell>>) {
-        for (i, cell) in cells.iter().enumerate() {
-            if let Some(active_cell) = cell {
-                if let Some(is_locked) = active_cell.is_locked {
-                    if is_locked {
-                        self.classes_is_locked[active_cell.class_index] =
-                            Some((active_cell.room, active_cell.period));
-                    } else {
-                        self.classes_is_locked[active_cell.class_index] = None;
-                    }
-                } else {
-                    self.classes_is_locked[active_cell.class_index] = None;
-                }
-            }
+    pub fn get_locks(&self) -> &Vec<Option<(usize, usize)>> {
+        return &self.classes_is_locked;
+    }
+
+    pub fn set_locks(&mut self, locks: &Vec<Option<(usize, usize)>>) {
+        for (class_index, lock) in locks.iter().enumerate().take(self.num_of_classes) {
+            self.classes_is_locked[class_index] = *lock;
         }
     }
 }
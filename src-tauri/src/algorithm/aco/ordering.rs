@@ -0,0 +1,244 @@
+use super::aco_solver::SolverRng;
+use super::graph::Graph;
+use crate::algorithm::constraint::Placements;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+//蟻が授業を置いていく順番
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ClassOrdering {
+    //一様に混ぜる
+    Random,
+    //空いている場所が最も少ない授業から置く(DSatur)。同じなら置きにくい方から
+    Saturation,
+    //置きにくいほど先に来やすいように混ぜる
+    WeightedDifficulty,
+}
+
+impl Default for ClassOrdering {
+    fn default() -> Self {
+        ClassOrdering::Random
+    }
+}
+
+//construct_pathの間だけ使う。Saturationでは授業ごとの空いている場所の数を持つ
+pub struct ClassOrder {
+    kind: ClassOrdering,
+    order: Vec<usize>,
+    position: usize,
+    remaining: Vec<usize>,
+    //一度返した授業。置けずに飛ばした授業を再び返さないようにする
+    taken: Vec<bool>,
+}
+
+impl ClassOrder {
+    //固定された授業を置いた後に作る。shuffledはRandomの順番で、同点の順にも使う
+    pub fn new(
+        kind: ClassOrdering,
+        graph: &Graph,
+        placements: &Placements,
+        mut shuffled: Vec<usize>,
+        rng: &mut SolverRng,
+    ) -> ClassOrder {
+        let difficulty = |v: usize| graph.get_class_slots(v).difficulty;
+        let mut remaining = Vec::new();
+        match kind {
+            ClassOrdering::Random => {}
+            ClassOrdering::Saturation => {
+                shuffled.sort_by(|&a, &b| difficulty(b).total_cmp(&difficulty(a)));
+                remaining = (0..shuffled.len())
+                    .map(|v| {
+                        let serial_size = graph.get_class(v).serial_size;
+                        graph
+                            .get_class_slots(v)
+                            .feasible()
+                            .iter()
+                            .filter(|x| placements.is_free(x.room, x.period, serial_size))
+                            .count()
+                    })
+                    .collect();
+            }
+            ClassOrdering::WeightedDifficulty => {
+                //重み付きの非復元抽出。u^(1/w)の大きい順に並べる
+                let mut keys = shuffled
+                    .iter()
+                    .map(|&v| {
+                        let weight = difficulty(v).max(f64::MIN_POSITIVE);
+                        (rng.gen::<f64>().powf(1.0 / weight), v)
+                    })
+                    .collect::<Vec<_>>();
+                keys.sort_by(|a, b| b.0.total_cmp(&a.0));
+                shuffled = keys.into_iter().map(|(_, v)| v).collect();
+            }
+        }
+        let taken = vec![false; shuffled.len()];
+        ClassOrder {
+            kind,
+            order: shuffled,
+            position: 0,
+            remaining,
+            taken,
+        }
+    }
+
+    //次に置く授業。全て返したらNone
+    pub fn next(&mut self, placements: &Placements) -> Option<usize> {
+        if self.kind == ClassOrdering::Saturation {
+            let res = self
+                .order
+                .iter()
+                .cloned()
+                .filter(|&v| !placements.is_placed(v) && !self.taken[v])
+                .min_by_key(|&v| self.remaining[v]);
+            if let Some(v) = res {
+                self.taken[v] = true;
+            }
+            return res;
+        }
+        while self.position < self.order.len() {
+            let v = self.order[self.position];
+            self.position += 1;
+            if !placements.is_placed(v) {
+                return Some(v);
+            }
+        }
+        None
+    }
+
+    //class_indexを(room, period)に置く直前に呼ぶ。塞がる場所を他の授業の数から引く
+    pub fn before_place(
+        &mut self,
+        graph: &Graph,
+        placements: &Placements,
+        class_index: usize,
+        room: usize,
+        period: usize,
+    ) {
+        if self.kind != ClassOrdering::Saturation {
+            return;
+        }
+        let span = placements.span(graph.get_class(class_index), period);
+        for v in 0..self.remaining.len() {
+            if v == class_index || placements.is_placed(v) || self.taken[v] {
+                continue;
+            }
+            let serial_size = graph.get_class(v).serial_size;
+            let blocked = graph
+                .get_class_slots(v)
+                .feasible()
+                .iter()
+                .filter(|x| {
+                    x.room == room
+                        && x.period < span.end
+                        && span.start < x.period + serial_size
+                        && placements.is_free(x.room, x.period, serial_size)
+                })
+                .count();
+            self.remaining[v] -= blocked;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::aco::aco_parameters::{AcoParameters, AcoSettings};
+    use crate::input::sample;
+    use rand::SeedableRng;
+
+    fn new_graph() -> Graph {
+        let input = sample::input();
+        Graph::new(
+            AcoParameters::new(&AcoSettings::default(), &input),
+            input.get_classes().clone(),
+            input.get_rooms().clone(),
+            input.get_teachers().clone(),
+            input.get_calendar().clone(),
+        )
+    }
+
+    fn num_of_classes(graph: &Graph) -> usize {
+        graph.constraint_context().classes.len()
+    }
+
+    fn count_free(graph: &Graph, placements: &Placements, class_index: usize) -> usize {
+        let serial_size = graph.get_class(class_index).serial_size;
+        graph
+            .get_class_slots(class_index)
+            .feasible()
+            .iter()
+            .filter(|x| placements.is_free(x.room, x.period, serial_size))
+            .count()
+    }
+
+    //空いている最初の場所に置きながら順番を最後まで引く。返した授業の順を返す
+    fn construct(kind: ClassOrdering, graph: &Graph, placements: &mut Placements) -> Vec<usize> {
+        let mut rng = SolverRng::seed_from_u64(1);
+        let shuffled = (0..num_of_classes(graph)).collect();
+        let mut order = ClassOrder::new(kind, graph, placements, shuffled, &mut rng);
+        let mut res = Vec::new();
+        while let Some(class_index) = order.next(placements) {
+            res.push(class_index);
+            let class = graph.get_class(class_index);
+            let slot = graph
+                .get_class_slots(class_index)
+                .all()
+                .iter()
+                .find(|x| placements.is_free(x.room, x.period, class.serial_size))
+                .cloned();
+            if let Some(slot) = slot {
+                order.before_place(graph, placements, class_index, slot.room, slot.period);
+                placements.place(class, slot.room, slot.period);
+            }
+            //Saturationの数は置くたびに引いて保つので、数え直した値と同じになる
+            if kind == ClassOrdering::Saturation {
+                for v in 0..order.remaining.len() {
+                    if !placements.is_placed(v) && !order.taken[v] {
+                        assert_eq!(order.remaining[v], count_free(graph, placements, v));
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    //どの順番でも、先に置いた授業を除く全ての授業を一度ずつ返す
+    #[test]
+    fn every_ordering_returns_each_class_once() {
+        let graph = new_graph();
+        for kind in [
+            ClassOrdering::Random,
+            ClassOrdering::Saturation,
+            ClassOrdering::WeightedDifficulty,
+        ] {
+            let mut placements = graph.constraint_context().new_placements();
+            placements.place(graph.get_class(0), 0, 0);
+            let mut order = construct(kind, &graph, &mut placements);
+            order.sort();
+            assert_eq!(order, (1..num_of_classes(&graph)).collect::<Vec<_>>());
+        }
+    }
+
+    //Saturationは空いている場所が最も少ない授業から返す
+    #[test]
+    fn saturation_starts_from_fewest_free_slots() {
+        let graph = new_graph();
+        let placements = graph.constraint_context().new_placements();
+        let mut rng = SolverRng::seed_from_u64(1);
+        let shuffled = (0..num_of_classes(&graph)).collect();
+        let mut order = ClassOrder::new(
+            ClassOrdering::Saturation,
+            &graph,
+            &placements,
+            shuffled,
+            &mut rng,
+        );
+        let first = order.next(&placements).unwrap();
+        let fewest = (0..num_of_classes(&graph))
+            .map(|v| count_free(&graph, &placements, v))
+            .min()
+            .unwrap();
+        assert_eq!(count_free(&graph, &placements, first), fewest);
+    }
+}
@@ -0,0 +1,202 @@
+use super::aco_parameters::{AcoParametersManager, AcoSettings, ParameterError};
+use crate::input::dataset::DatasetPaths;
+use crate::input::{get_config_dir, InputManager};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+const PRESETS_FILE_NAME: &str = "aco_presets.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterPreset {
+    pub name: String,
+    pub settings: AcoSettings,
+}
+
+//保存済みのプリセットと、データセットごとに選んだプリセット名
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetStore {
+    pub presets: Vec<ParameterPreset>,
+    #[serde(default)]
+    pub dataset_presets: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetList {
+    pub presets: Vec<ParameterPreset>,
+    //読み込み中のデータセットで選ばれているプリセット
+    pub selected: Option<String>,
+}
+
+fn is_builtin(name: &str) -> bool {
+    builtin_presets().iter().any(|x| x.name == name)
+}
+
+fn builtin_presets() -> Vec<ParameterPreset> {
+    vec![
+        ParameterPreset {
+            name: "default".to_string(),
+            settings: AcoSettings::default(),
+        },
+        ParameterPreset {
+            name: "fast draft".to_string(),
+            settings: AcoSettings {
+                num_of_ants: 3,
+                max_iterations: 20,
+                super_not_change: 200,
+                ..AcoSettings::default()
+            },
+        },
+        ParameterPreset {
+            name: "overnight quality".to_string(),
+            settings: AcoSettings {
+                num_of_ants: 20,
+                max_iterations: 100000,
+                super_not_change: 5000,
+                ..AcoSettings::default()
+            },
+        },
+    ]
+}
+
+impl PresetStore {
+    //ファイルがなければ組み込みのプリセットだけを返す
+    //読めないファイルは次の保存で上書きしないよう、エラーにして返す
+    //組み込みのプリセットはファイルに同じ名前があってもコードの値にする
+    pub fn load(config_dir: &Path) -> Result<PresetStore, Box<dyn Error>> {
+        let mut res: PresetStore = match std::fs::read_to_string(config_dir.join(PRESETS_FILE_NAME))
+        {
+            Ok(text) => serde_json::from_str(&text)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PresetStore::default(),
+            Err(e) => return Err(Box::new(e)),
+        };
+        res.presets.retain(|x| !is_builtin(&x.name));
+        res.presets.extend(builtin_presets());
+        Ok(res)
+    }
+
+    //組み込みのプリセットは書き出さない
+    pub fn save(&self, config_dir: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::create_dir_all(config_dir)?;
+        let store = PresetStore {
+            presets: self
+                .presets
+                .iter()
+                .filter(|x| !is_builtin(&x.name))
+                .cloned()
+                .collect(),
+            dataset_presets: self.dataset_presets.clone(),
+        };
+        let text = serde_json::to_string_pretty(&store)?;
+        std::fs::write(config_dir.join(PRESETS_FILE_NAME), text)?;
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ParameterPreset> {
+        self.presets.iter().find(|x| x.name == name)
+    }
+
+    pub fn dataset_settings(&self, paths: &DatasetPaths) -> Option<AcoSettings> {
+        let name = self.dataset_presets.get(&paths.key())?;
+        self.find(name).map(|x| x.settings.clone())
+    }
+
+    fn to_list(&self, paths: Option<&DatasetPaths>) -> PresetList {
+        PresetList {
+            presets: self.presets.clone(),
+            selected: paths.and_then(|x| self.dataset_presets.get(&x.key()).cloned()),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn handle_get_presets(
+    app_handle: tauri::AppHandle,
+    input_manager: tauri::State<'_, InputManager>,
+) -> Result<PresetList, String> {
+    let store = PresetStore::load(&get_config_dir(&app_handle)).map_err(|e| e.to_string())?;
+    let paths = input_manager.dataset_paths.lock().unwrap();
+    Ok(store.to_list(paths.as_ref()))
+}
+
+#[tauri::command]
+pub fn handle_save_preset(
+    app_handle: tauri::AppHandle,
+    input_manager: tauri::State<'_, InputManager>,
+    name: String,
+    settings: AcoSettings,
+) -> Result<PresetList, Vec<ParameterError>> {
+    println!("called handle_save_preset {}", name);
+    //組み込みのプリセットは読み込むたびに元に戻るので、同じ名前では保存しない
+    if is_builtin(&name) {
+        return Err(vec![ParameterError {
+            field: "preset".to_string(),
+            value: name.clone(),
+            message: format!("{} is a built-in preset", name),
+        }]);
+    }
+    settings.validate()?;
+    let file_error = |e: Box<dyn Error>| {
+        vec![ParameterError {
+            field: "preset".to_string(),
+            value: PRESETS_FILE_NAME.to_string(),
+            message: e.to_string(),
+        }]
+    };
+    let config_dir = get_config_dir(&app_handle);
+    let mut store = PresetStore::load(&config_dir).map_err(file_error)?;
+    match store.presets.iter_mut().find(|x| x.name == name) {
+        Some(preset) => preset.settings = settings,
+        None => store.presets.push(ParameterPreset { name, settings }),
+    }
+    store.save(&config_dir).map_err(file_error)?;
+    let paths = input_manager.dataset_paths.lock().unwrap();
+    Ok(store.to_list(paths.as_ref()))
+}
+
+#[tauri::command]
+pub fn handle_delete_preset(
+    app_handle: tauri::AppHandle,
+    input_manager: tauri::State<'_, InputManager>,
+    name: String,
+) -> Result<PresetList, String> {
+    println!("called handle_delete_preset {}", name);
+    if is_builtin(&name) {
+        return Err(format!("{} is a built-in preset", name));
+    }
+    let config_dir = get_config_dir(&app_handle);
+    let mut store = PresetStore::load(&config_dir).map_err(|e| e.to_string())?;
+    store.presets.retain(|x| x.name != name);
+    store.dataset_presets.retain(|_, x| *x != name);
+    store.save(&config_dir).map_err(|e| e.to_string())?;
+    let paths = input_manager.dataset_paths.lock().unwrap();
+    Ok(store.to_list(paths.as_ref()))
+}
+
+//プリセットを現在の設定にし、読み込み中のデータセットの既定として記録する
+#[tauri::command]
+pub fn handle_select_preset(
+    app_handle: tauri::AppHandle,
+    input_manager: tauri::State<'_, InputManager>,
+    aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
+    name: String,
+) -> Result<AcoSettings, String> {
+    println!("called handle_select_preset {}", name);
+    let config_dir = get_config_dir(&app_handle);
+    let mut store = PresetStore::load(&config_dir).map_err(|e| e.to_string())?;
+    let settings = store
+        .find(&name)
+        .ok_or(format!("preset {} not found", name))?
+        .settings
+        .clone();
+    if let Some(paths) = input_manager.dataset_paths.lock().unwrap().as_ref() {
+        store.dataset_presets.insert(paths.key(), name);
+        store.save(&config_dir).map_err(|e| e.to_string())?;
+    }
+    *aco_parameters_manager.settings.lock().unwrap() = settings.clone();
+    Ok(settings)
+}
@@ -0,0 +1,81 @@
+use super::aco_parameters::{check_f64, check_usize, ParameterError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PheromoneStrategy {
+    //全ての蟻が置く
+    AntSystem,
+    //その世代の最良の蟻だけが置く
+    IterationBest,
+    //全ての蟻に加えて、これまでの最良(super_ant)が重みを付けて置く
+    Elitist,
+    //上位の蟻が順位に応じた重みで置き、super_antが最大の重みで置く
+    RankBased,
+    //super_antの通った場所だけを更新し、蟻が1つ置くたびにその場所を初期値へ近づける
+    AntColonySystem,
+}
+
+impl Default for PheromoneStrategy {
+    fn default() -> Self {
+        PheromoneStrategy::AntSystem
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PheromoneSettings {
+    pub strategy: PheromoneStrategy,
+    //Elitistでsuper_antの量に掛ける値
+    pub elitist_weight: f64,
+    //RankBasedで置く順位の数。super_antを含む
+    pub rank_size: usize,
+    //ACSの局所更新で初期値へ近づける割合
+    pub acs_local_rate: f64,
+    //ACSで確率ではなく最も良い場所を選ぶ確率
+    pub acs_exploitation: f64,
+}
+
+impl Default for PheromoneSettings {
+    fn default() -> Self {
+        PheromoneSettings {
+            strategy: PheromoneStrategy::default(),
+            elitist_weight: 3.0,
+            rank_size: 3,
+            acs_local_rate: 0.1,
+            acs_exploitation: 0.9,
+        }
+    }
+}
+
+impl PheromoneSettings {
+    pub fn validate(&self, errors: &mut Vec<ParameterError>) {
+        check_f64(
+            errors,
+            "pheromone.elitistWeight",
+            self.elitist_weight,
+            0.0,
+            1000.0,
+        );
+        check_usize(errors, "pheromone.rankSize", self.rank_size, 1, 1000);
+        check_f64(
+            errors,
+            "pheromone.acsLocalRate",
+            self.acs_local_rate,
+            0.0,
+            1.0,
+        );
+        check_f64(
+            errors,
+            "pheromone.acsExploitation",
+            self.acs_exploitation,
+            0.0,
+            1.0,
+        );
+    }
+
+    //ACSのときだけ蟻の構築に手を入れる
+    pub fn is_acs(&self) -> bool {
+        self.strategy == PheromoneStrategy::AntColonySystem
+    }
+}
@@ -0,0 +1,201 @@
+use crate::input::class::Class;
+
+//置ける場所がない組を表す
+const NONE: u32 = u32::MAX;
+
+//授業ごとに候補の教室と開始できる時限だけを持つフェロモン表。
+//授業cの値はoffsets[c]..offsets[c + 1]に、候補の教室ごとnum_of_starts[c]個ずつ並ぶ
+#[derive(Clone)]
+pub struct PheromoneTable {
+    offsets: Vec<usize>,
+    num_of_starts: Vec<usize>,
+    //room_positions[c * num_of_rooms + room] = 候補の教室の何番目か
+    room_positions: Vec<u32>,
+    num_of_rooms: usize,
+    pheromone: Vec<f64>,
+    next_pheromone: Vec<f64>,
+}
+
+impl PheromoneTable {
+    pub fn new(
+        classes: &Vec<Class>,
+        num_of_rooms: usize,
+        num_of_periods: usize,
+        initial: f64,
+    ) -> PheromoneTable {
+        let mut offsets = vec![0];
+        let mut num_of_starts = Vec::new();
+        let mut room_positions = vec![NONE; classes.len() * num_of_rooms];
+        for (class_index, class) in classes.iter().enumerate() {
+            let starts = (num_of_periods + 1).saturating_sub(class.serial_size);
+            let mut num_of_candidates = 0;
+            for &room in class.room_candidates_indexes.iter() {
+                if room >= num_of_rooms {
+                    continue;
+                }
+                let position = &mut room_positions[class_index * num_of_rooms + room];
+                if *position == NONE {
+                    *position = num_of_candidates as u32;
+                    num_of_candidates += 1;
+                }
+            }
+            num_of_starts.push(starts);
+            offsets.push(offsets[class_index] + num_of_candidates * starts);
+        }
+        let size = offsets[classes.len()];
+        PheromoneTable {
+            offsets,
+            num_of_starts,
+            room_positions,
+            num_of_rooms,
+            pheromone: vec![initial; size],
+            next_pheromone: vec![0.0; size],
+        }
+    }
+
+    //候補の教室でない、または開始できない時限ならNone
+    fn index(&self, class_index: usize, room: usize, period: usize) -> Option<usize> {
+        if room >= self.num_of_rooms || period >= self.num_of_starts[class_index] {
+            return None;
+        }
+        let position = self.room_positions[class_index * self.num_of_rooms + room];
+        if position == NONE {
+            return None;
+        }
+        Some(
+            self.offsets[class_index]
+                + position as usize * self.num_of_starts[class_index]
+                + period,
+        )
+    }
+
+    //持っていない場所は0とする
+    pub fn get(&self, class_index: usize, room: usize, period: usize) -> f64 {
+        match self.index(class_index, room, period) {
+            Some(i) => self.pheromone[i],
+            None => 0.0,
+        }
+    }
+
+    pub fn set(&mut self, class_index: usize, room: usize, period: usize, pheromone: f64) {
+        if let Some(i) = self.index(class_index, room, period) {
+            self.pheromone[i] = pheromone;
+        }
+    }
+
+    pub fn add_next(&mut self, class_index: usize, room: usize, period: usize, pheromone: f64) {
+        if let Some(i) = self.index(class_index, room, period) {
+            self.next_pheromone[i] += pheromone;
+        }
+    }
+
+    //授業1つ分の値
+    pub fn class_values(&self, class_index: usize) -> &[f64] {
+        &self.pheromone[self.offsets[class_index]..self.offsets[class_index + 1]]
+    }
+
+    pub fn class_values_mut(&mut self, class_index: usize) -> &mut [f64] {
+        &mut self.pheromone[self.offsets[class_index]..self.offsets[class_index + 1]]
+    }
+
+    pub fn fill(&mut self, pheromone: f64) {
+        self.pheromone.fill(pheromone);
+    }
+
+    pub fn clear_next(&mut self) {
+        self.next_pheromone.fill(0.0);
+    }
+
+    //pheromone = pheromone * rou + next_pheromoneを[tau_min, tau_max]に収める
+    pub fn evaporate(&mut self, rou: f64, tau_min: f64, tau_max: f64) {
+        for (pheromone, next) in self.pheromone.iter_mut().zip(self.next_pheromone.iter()) {
+            *pheromone = (*pheromone * rou + next).max(tau_min).min(tau_max);
+        }
+    }
+
+    //pheromone += rate * (target - pheromone)
+    pub fn smooth(&mut self, rate: f64, target: f64) {
+        for pheromone in self.pheromone.iter_mut() {
+            *pheromone += rate * (target - *pheromone);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::sample;
+
+    const NUM_OF_ROOMS: usize = 3;
+    const NUM_OF_PERIODS: usize = 12;
+
+    fn new_table() -> (Vec<Class>, PheromoneTable) {
+        let classes = sample::input().get_classes().clone();
+        let table = PheromoneTable::new(&classes, NUM_OF_ROOMS, NUM_OF_PERIODS, 1.0);
+        (classes, table)
+    }
+
+    fn is_stored(class: &Class, room: usize, period: usize) -> bool {
+        class.room_candidates_indexes.contains(&room)
+            && period + class.serial_size <= NUM_OF_PERIODS
+    }
+
+    //候補の教室と開始できる時限だけを持ち、重複した候補は1つにまとめる
+    #[test]
+    fn stores_only_candidate_slots() {
+        let (classes, table) = new_table();
+        for (class_index, class) in classes.iter().enumerate() {
+            let mut rooms = class.room_candidates_indexes.clone();
+            rooms.sort();
+            rooms.dedup();
+            let starts = NUM_OF_PERIODS + 1 - class.serial_size;
+            assert_eq!(table.class_values(class_index).len(), rooms.len() * starts);
+        }
+    }
+
+    //場所ごとに別の値を入れて、互いに上書きしないことと、持っていない場所は0のままなことを確かめる
+    #[test]
+    fn set_and_get_each_slot() {
+        let (classes, mut table) = new_table();
+        let value = |class_index: usize, room: usize, period: usize| {
+            ((class_index * NUM_OF_ROOMS + room) * NUM_OF_PERIODS + period) as f64 + 2.0
+        };
+        for class_index in 0..classes.len() {
+            for room in 0..=NUM_OF_ROOMS {
+                for period in 0..=NUM_OF_PERIODS {
+                    table.set(class_index, room, period, value(class_index, room, period));
+                }
+            }
+        }
+        for (class_index, class) in classes.iter().enumerate() {
+            for room in 0..=NUM_OF_ROOMS {
+                for period in 0..=NUM_OF_PERIODS {
+                    let expected = if is_stored(class, room, period) {
+                        value(class_index, room, period)
+                    } else {
+                        0.0
+                    };
+                    assert_eq!(table.get(class_index, room, period), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn evaporate_adds_next_and_clamps() {
+        let (_, mut table) = new_table();
+        table.set(0, 0, 0, 4.0);
+        table.add_next(0, 0, 0, 1.0);
+        table.add_next(0, 0, 0, 1.0);
+        table.set(0, 0, 1, 10.0);
+        table.add_next(0, 0, 1, 10.0);
+        table.evaporate(0.5, 0.8, 5.0);
+        assert_eq!(table.get(0, 0, 0), 4.0);
+        assert_eq!(table.get(0, 0, 1), 5.0);
+        assert_eq!(table.get(0, 0, 2), 0.8);
+        //置いた量は次の世代に持ち越さない
+        table.clear_next();
+        table.evaporate(1.0, 0.0, 100.0);
+        assert_eq!(table.get(0, 0, 0), 4.0);
+    }
+}
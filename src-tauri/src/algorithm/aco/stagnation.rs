@@ -0,0 +1,67 @@
+use super::aco_parameters::{check_f64, check_usize, ParameterError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RestartMode {
+    //フェロモンを初期値に戻す
+    Reset,
+    //今のフェロモンを初期値の方へsmoothing_rateだけ寄せる
+    Smooth,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StagnationSettings {
+    pub restart: RestartMode,
+    pub smoothing_rate: f64,
+    //フェロモンの平均エントロピー(0から1)がこれを下回ったら停滞とみなす。0なら見ない
+    pub min_entropy: f64,
+    //再出発の後、この世代数はエントロピーを見ない。Smoothで寄せ切れずに毎世代再出発するのを防ぐ
+    pub cooldown: usize,
+}
+
+impl Default for StagnationSettings {
+    fn default() -> Self {
+        StagnationSettings {
+            restart: RestartMode::Reset,
+            smoothing_rate: 0.5,
+            min_entropy: 0.1,
+            cooldown: 50,
+        }
+    }
+}
+
+impl StagnationSettings {
+    pub fn validate(&self, errors: &mut Vec<ParameterError>) {
+        check_f64(
+            errors,
+            "stagnation.smoothingRate",
+            self.smoothing_rate,
+            0.0,
+            1.0,
+        );
+        check_f64(errors, "stagnation.minEntropy", self.min_entropy, 0.0, 1.0);
+        check_usize(errors, "stagnation.cooldown", self.cooldown, 0, usize::MAX);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum StagnationReason {
+    //super_antがsuper_not_change世代変わらなかった
+    NoImprovement,
+    //フェロモンが一部の場所に偏った
+    LowEntropy,
+}
+
+//再出発の記録。実行結果に載せる
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Restart {
+    pub iteration: usize,
+    pub reason: StagnationReason,
+    pub mode: RestartMode,
+    pub entropy: f64,
+    pub iterations_without_improvement: usize,
+}
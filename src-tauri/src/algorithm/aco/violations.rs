@@ -12,7 +12,7 @@ impl Violations {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CellsViolation {
     pub is_violated: bool,
@@ -20,4 +20,8 @@ pub struct CellsViolation {
     pub same_teacher_same_time: Vec<Violations>,
     pub capacity_over: Vec<Violations>,
     pub strabble_days: Vec<Violations>,
+    #[serde(default)]
+    pub absent_days: Vec<Violations>,
+    #[serde(default)]
+    pub room_not_candidate: Vec<Violations>,
 }
@@ -0,0 +1,235 @@
+use super::aco::aco_parameters::{check_f64, check_usize, AcoParameters, ParameterError};
+use super::aco::aco_solver::{new_seed, SolverRng};
+use super::constraint::{self, ConstraintContext, Placements, Score, ScoreBreakdown};
+use super::local_search::{self, Move};
+use super::solver::{self, Solver, SolverKind};
+use crate::input::Input;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnnealingSettings {
+    pub initial_temperature: f64,
+    //1反復ごとに温度に掛ける値
+    pub cooling_rate: f64,
+    //温度がこれを下回ったら初期温度に戻す
+    pub min_temperature: f64,
+    //1反復で試す移動の数
+    pub moves_per_step: usize,
+}
+
+impl Default for AnnealingSettings {
+    fn default() -> Self {
+        AnnealingSettings {
+            initial_temperature: 10.0,
+            cooling_rate: 0.995,
+            min_temperature: 0.01,
+            moves_per_step: 200,
+        }
+    }
+}
+
+impl AnnealingSettings {
+    pub fn validate(&self, errors: &mut Vec<ParameterError>) {
+        check_f64(
+            errors,
+            "annealing.initialTemperature",
+            self.initial_temperature,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+        );
+        check_f64(errors, "annealing.coolingRate", self.cooling_rate, 0.0, 1.0);
+        check_f64(
+            errors,
+            "annealing.minTemperature",
+            self.min_temperature,
+            f64::MIN_POSITIVE,
+            self.initial_temperature,
+        );
+        check_usize(
+            errors,
+            "annealing.movesPerStep",
+            self.moves_per_step,
+            1,
+            1_000_000,
+        );
+    }
+}
+
+//焼きなまし法。近傍は局所探索と同じ移動と入れ替え
+#[derive(Clone)]
+pub struct AnnealingSolver {
+    parameters: AcoParameters,
+    input: Input,
+    locks: Vec<Option<(usize, usize)>>,
+    current: Option<Placements>,
+    //currentのコスト。移動のたびに増分を足す
+    current_breakdown: ScoreBreakdown,
+    best: Option<Placements>,
+    best_breakdown: ScoreBreakdown,
+    temperature: f64,
+    seed: u64,
+    rng: SolverRng,
+}
+
+//温度と比べるための1つの値。hardは重みを掛けた値なのでそのまま足す
+fn energy(old: &Score, new: &Score) -> f64 {
+    (new.hard - old.hard) + (new.soft - old.soft)
+}
+
+impl AnnealingSolver {
+    pub fn new(mut parameters: AcoParameters, input: Input) -> AnnealingSolver {
+        let seed = parameters.seed.unwrap_or_else(new_seed);
+        parameters.seed = Some(seed);
+        let num_of_classes = input.get_classes().len();
+        AnnealingSolver {
+            temperature: parameters.annealing.initial_temperature,
+            parameters,
+            input,
+            locks: vec![None; num_of_classes],
+            current: None,
+            current_breakdown: ScoreBreakdown::default(),
+            best: None,
+            best_breakdown: ScoreBreakdown::default(),
+            seed,
+            rng: SolverRng::seed_from_u64(seed),
+        }
+    }
+
+    //movableは固定されていない授業。動かせる授業がなければNone
+    fn random_move(&mut self, placements: &Placements, movable: &Vec<usize>) -> Option<Move> {
+        let class_index = *movable.choose(&mut self.rng)?;
+        let class = &self.input.get_classes()[class_index];
+        let room = class.room_candidates_indexes.choose(&mut self.rng).copied();
+        let period = self.rng.gen_range(0..placements.num_of_periods());
+        if !placements.is_placed(class_index) {
+            return room.map(|room| Move::Insert {
+                class_index,
+                to: [room, period],
+            });
+        }
+        match room {
+            Some(room) if self.rng.gen::<bool>() => Some(Move::Relocate {
+                class_index,
+                to: [room, period],
+            }),
+            _ => Some(Move::Swap {
+                class_a: class_index,
+                class_b: *movable.choose(&mut self.rng)?,
+            }),
+        }
+    }
+}
+
+impl Solver for AnnealingSolver {
+    fn kind(&self) -> SolverKind {
+        SolverKind::SimulatedAnnealing
+    }
+
+    fn initialize(&mut self) {
+        self.rng = SolverRng::seed_from_u64(self.seed);
+        let ctx = ConstraintContext::new(&self.input, &self.parameters);
+        let placements = solver::random_placements(&ctx, &self.locks, &mut self.rng);
+        self.current_breakdown = constraint::breakdown(&ctx, &placements);
+        self.best_breakdown = self.current_breakdown;
+        self.best = Some(placements.clone());
+        self.current = Some(placements);
+        self.temperature = self.parameters.annealing.initial_temperature;
+    }
+
+    fn step(&mut self) {
+        if self.current.is_none() {
+            self.initialize();
+        }
+        let mut current = self.current.take().unwrap();
+        let movable = (0..self.locks.len())
+            .filter(|&class_index| self.locks[class_index].is_none())
+            .collect::<Vec<usize>>();
+        for _ in 0..self.parameters.annealing.moves_per_step {
+            let next = match self.random_move(&current, &movable) {
+                Some(next) => next,
+                None => continue,
+            };
+            let ctx = ConstraintContext::new(&self.input, &self.parameters);
+            let (old, new) = match local_search::move_delta(&ctx, &mut current, &next) {
+                Some(res) => res,
+                None => continue,
+            };
+            let diff = energy(&old, &new);
+            if diff <= 0.0 || self.rng.gen::<f64>() < (-diff / self.temperature).exp() {
+                local_search::apply(&ctx, &mut current, &mut self.current_breakdown, next);
+            }
+        }
+        let score = self.current_breakdown.score();
+        if score.is_better_than(&self.best_breakdown.score()) {
+            self.best_breakdown = self.current_breakdown;
+            self.best = Some(current.clone());
+        }
+        self.current = Some(current);
+        self.temperature *= self.parameters.annealing.cooling_rate;
+        if self.temperature < self.parameters.annealing.min_temperature {
+            self.temperature = self.parameters.annealing.initial_temperature;
+        }
+    }
+
+    fn best_solution(&self) -> Option<&Placements> {
+        self.best.as_ref()
+    }
+
+    fn breakdown(&self) -> ScoreBreakdown {
+        self.best_breakdown
+    }
+
+    fn current_score(&self) -> Score {
+        self.current_breakdown.score()
+    }
+
+    //最後は最も良かった解から続きを探す
+    fn finish_run(&mut self) {
+        if let Some(best) = &self.best {
+            self.current = Some(best.clone());
+            self.current_breakdown = self.best_breakdown;
+        }
+    }
+
+    fn get_locks(&self) -> &Vec<Option<(usize, usize)>> {
+        &self.locks
+    }
+
+    //固定が変わったら今の解と最良解に反映する。作り直さないので最良解は残る
+    fn set_locks(&mut self, locks: &Vec<Option<(usize, usize)>>) {
+        let mut is_changed = false;
+        for (class_index, lock) in locks.iter().enumerate().take(self.locks.len()) {
+            if self.locks[class_index] != *lock {
+                self.locks[class_index] = *lock;
+                is_changed = true;
+            }
+        }
+        if !is_changed {
+            return;
+        }
+        let ctx = ConstraintContext::new(&self.input, &self.parameters);
+        if let Some(current) = self.current.as_mut() {
+            solver::apply_locks(&ctx, current, &self.locks, &mut self.rng);
+            self.current_breakdown = constraint::breakdown(&ctx, current);
+        }
+        if let Some(best) = self.best.as_mut() {
+            solver::apply_locks(&ctx, best, &self.locks, &mut self.rng);
+            self.best_breakdown = constraint::breakdown(&ctx, best);
+        }
+    }
+
+    fn get_parameters(&self) -> &AcoParameters {
+        &self.parameters
+    }
+
+    fn get_input(&self) -> &Input {
+        &self.input
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.seed
+    }
+}
@@ -0,0 +1,452 @@
+pub mod placements;
+pub mod rules;
+
+use super::aco::aco_parameters::{AcoParameters, ConstraintWeights};
+use super::aco::violations::{CellsViolation, Violations};
+use crate::input::calendar::Calendar;
+use crate::input::class::Class;
+use crate::input::room::Room;
+use crate::input::teacher::Teacher;
+use crate::input::Input;
+pub use placements::Placements;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConstraintKind {
+    Capacity,
+    SameTeacher,
+    SameStudentGroup,
+    AbsentDays,
+    StraddleDays,
+    Collection,
+    RoomNotCandidate,
+    Unassigned,
+}
+
+//制約の計算に必要な入力と重み
+pub struct ConstraintContext<'a> {
+    pub classes: &'a Vec<Class>,
+    pub rooms: &'a Vec<Room>,
+    pub teachers: &'a Vec<Teacher>,
+    pub calendar: &'a Calendar,
+    pub weights: &'a ConstraintWeights,
+    pub size_of_frame: usize,
+    pub num_of_students: usize,
+}
+
+impl<'a> ConstraintContext<'a> {
+    pub fn new(input: &'a Input, parameters: &'a AcoParameters) -> ConstraintContext<'a> {
+        ConstraintContext {
+            classes: input.get_classes(),
+            rooms: input.get_rooms(),
+            teachers: input.get_teachers(),
+            calendar: input.get_calendar(),
+            weights: &parameters.weights,
+            size_of_frame: parameters.size_of_frame,
+            num_of_students: parameters.num_of_students,
+        }
+    }
+
+    pub fn new_placements(&self) -> Placements {
+        Placements::new(
+            self.classes.len(),
+            self.rooms.len(),
+            self.calendar.num_of_periods(),
+            self.teachers.len(),
+            self.num_of_students,
+        )
+    }
+}
+
+//1つの規則。ACOの評価とTimeTableの違反表示の両方がこれを使う
+pub trait Constraint: Sync {
+    fn kind(&self) -> ConstraintKind;
+
+    //守らないと使えない時間割になる規則ならtrue。評価ではsoftより先に比べる
+    fn is_hard(&self) -> bool;
+
+    //配置全体のコスト
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64;
+
+    //classを外した配置に、classを(room, period)で置いたときのコストの増分。
+    //classが置かれていても、その配置はないものとして数える
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        room: usize,
+        period: usize,
+    ) -> f64;
+
+    //コストが置く授業と場所だけで決まり、他の授業の配置によらないならtrue
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    //deltaが取りうる最小値。ACOのヒューリスティックを正に保つのに使う
+    fn min_delta(&self, _ctx: &ConstraintContext, _class_index: usize) -> f64 {
+        0.0
+    }
+
+    //配置済みのclassが関わる違反。相手の授業があるセルを返す
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations>;
+}
+
+//規則を増やすときは型を書いてここに並べる
+pub static CONSTRAINTS: [&dyn Constraint; 8] = [
+    &rules::Capacity,
+    &rules::SameTeacher,
+    &rules::SameStudentGroup,
+    &rules::AbsentDays,
+    &rules::StraddleDays,
+    &rules::Collection,
+    &rules::RoomNotCandidate,
+    &rules::Unassigned,
+];
+
+//hardとsoftのコストを分けて持つ評価値。hardが小さい方を常に良いとする
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Score {
+    pub hard: f64,
+    pub soft: f64,
+}
+
+impl std::ops::Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score {
+            hard: self.hard + other.hard,
+            soft: self.soft + other.soft,
+        }
+    }
+}
+
+impl Score {
+    fn add(&mut self, constraint: &dyn Constraint, value: f64) {
+        if constraint.is_hard() {
+            self.hard += value;
+        } else {
+            self.soft += value;
+        }
+    }
+
+    //違反がなければ1になる、従来の評価値
+    pub fn total(&self) -> f64 {
+        1.0 + self.hard + self.soft
+    }
+
+    pub fn is_feasible(&self) -> bool {
+        self.hard <= 0.0
+    }
+
+    pub fn is_better_than(&self, other: &Score) -> bool {
+        if self.hard != other.hard {
+            return self.hard < other.hard;
+        }
+        self.soft < other.soft
+    }
+}
+
+//規則ごとのコスト。CONSTRAINTSと同じ順に並ぶ
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreBreakdown {
+    pub costs: [f64; CONSTRAINTS.len()],
+}
+
+impl ScoreBreakdown {
+    pub fn score(&self) -> Score {
+        let mut res = Score::default();
+        for (constraint, cost) in CONSTRAINTS.iter().zip(self.costs.iter()) {
+            res.add(*constraint, *cost);
+        }
+        res
+    }
+
+    //未配置のclassを(room, period)に置く前に呼ぶ。置いた後のコストになる
+    pub fn add_placement(
+        &mut self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        room: usize,
+        period: usize,
+    ) {
+        for (constraint, cost) in CONSTRAINTS.iter().zip(self.costs.iter_mut()) {
+            *cost += constraint.delta(ctx, placements, class_index, room, period);
+        }
+    }
+
+    //配置済みのclassを(room, period)から外した後に呼ぶ。外した後のコストになる
+    pub fn remove_placement(
+        &mut self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        room: usize,
+        period: usize,
+    ) {
+        for (constraint, cost) in CONSTRAINTS.iter().zip(self.costs.iter_mut()) {
+            *cost -= constraint.delta(ctx, placements, class_index, room, period);
+        }
+    }
+}
+
+pub fn breakdown(ctx: &ConstraintContext, placements: &Placements) -> ScoreBreakdown {
+    let mut res = ScoreBreakdown::default();
+    for (constraint, cost) in CONSTRAINTS.iter().zip(res.costs.iter_mut()) {
+        *cost = constraint.cost(ctx, placements);
+    }
+    res
+}
+
+//他の授業によらない規則だけの増分。授業ごとに前もって求めておける
+pub fn static_delta(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+    room: usize,
+    period: usize,
+) -> Score {
+    let mut res = Score::default();
+    for constraint in CONSTRAINTS.iter().filter(|x| x.is_static()) {
+        res.add(
+            *constraint,
+            constraint.delta(ctx, placements, class_index, room, period)
+                - constraint.min_delta(ctx, class_index),
+        );
+    }
+    res
+}
+
+//他の授業との関係で決まる規則だけの増分
+pub fn dynamic_delta(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+    room: usize,
+    period: usize,
+) -> Score {
+    let mut res = Score::default();
+    for constraint in CONSTRAINTS.iter().filter(|x| !x.is_static()) {
+        res.add(
+            *constraint,
+            constraint.delta(ctx, placements, class_index, room, period)
+                - constraint.min_delta(ctx, class_index),
+        );
+    }
+    res
+}
+
+//未配置のclassを(room, period)に置いたときの増分。最も良い置き方で0になるようずらす
+pub fn slot_delta(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+    room: usize,
+    period: usize,
+) -> Score {
+    static_delta(ctx, placements, class_index, room, period)
+        + dynamic_delta(ctx, placements, class_index, room, period)
+}
+
+pub fn edge_length(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+    room: usize,
+    period: usize,
+) -> f64 {
+    slot_delta(ctx, placements, class_index, room, period).total()
+}
+
+//配置済みのclassの今の場所のedge_length。deltaは自分の配置を数えないので外さずに測れる
+pub fn placed_edge_length(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+) -> Option<f64> {
+    let [room, period] = placements.get(class_index)?;
+    Some(edge_length(ctx, placements, class_index, room, period))
+}
+
+pub fn violations_of_kind(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    kind: ConstraintKind,
+) -> Vec<Violations> {
+    let mut res = Vec::new();
+    for constraint in CONSTRAINTS.iter().filter(|x| x.kind() == kind) {
+        for class_index in 0..ctx.classes.len() {
+            if placements.is_placed(class_index) {
+                res.append(&mut constraint.violations(ctx, placements, class_index));
+            }
+        }
+    }
+    res
+}
+
+pub fn cell_violations(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+) -> CellsViolation {
+    let mut res = CellsViolation::default();
+    for constraint in CONSTRAINTS.iter() {
+        let mut violations = constraint.violations(ctx, placements, class_index);
+        let target = match constraint.kind() {
+            ConstraintKind::Capacity => &mut res.capacity_over,
+            ConstraintKind::SameTeacher => &mut res.same_teacher_same_time,
+            ConstraintKind::SameStudentGroup => &mut res.same_student_same_time,
+            ConstraintKind::AbsentDays => &mut res.absent_days,
+            ConstraintKind::StraddleDays => &mut res.strabble_days,
+            ConstraintKind::RoomNotCandidate => &mut res.room_not_candidate,
+            ConstraintKind::Collection | ConstraintKind::Unassigned => continue,
+        };
+        target.append(&mut violations);
+    }
+    res.is_violated = !res.capacity_over.is_empty()
+        || !res.same_teacher_same_time.is_empty()
+        || !res.same_student_same_time.is_empty()
+        || !res.absent_days.is_empty()
+        || !res.strabble_days.is_empty()
+        || !res.room_not_candidate.is_empty();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::aco::aco_parameters::AcoSettings;
+    use crate::algorithm::aco::aco_solver::SolverRng;
+    use crate::input::sample;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    const EPSILON: f64 = 1e-9;
+
+    //枠の空きも数えるよう、1日を2つの枠に分ける
+    fn parameters(input: &Input) -> AcoParameters {
+        let mut parameters = AcoParameters::new(&AcoSettings::default(), input);
+        parameters.size_of_frame = 2;
+        parameters
+    }
+
+    //空いている場所に、候補でない教室も含めて無作為に置く
+    fn random_placements(ctx: &ConstraintContext, rng: &mut SolverRng) -> Placements {
+        let mut placements = ctx.new_placements();
+        for class in ctx.classes.iter() {
+            if rng.gen::<f64>() < 0.3 {
+                continue;
+            }
+            let room = rng.gen_range(0..ctx.rooms.len());
+            let period = rng.gen_range(0..placements.num_of_periods());
+            if placements.is_free(room, period, class.serial_size) {
+                placements.place(class, room, period);
+            }
+        }
+        placements
+    }
+
+    fn free_slots(
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class: &Class,
+    ) -> Vec<[usize; 2]> {
+        let mut res = Vec::new();
+        for room in 0..ctx.rooms.len() {
+            for period in 0..placements.num_of_periods() {
+                if placements.is_free(room, period, class.serial_size) {
+                    res.push([room, period]);
+                }
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn delta_matches_change_of_cost() {
+        let input = sample::input();
+        let parameters = parameters(&input);
+        let ctx = ConstraintContext::new(&input, &parameters);
+        for seed in 0..50 {
+            let mut rng = SolverRng::seed_from_u64(seed);
+            let placements = random_placements(&ctx, &mut rng);
+            for class in ctx.classes.iter() {
+                if placements.is_placed(class.index) {
+                    continue;
+                }
+                let slots = free_slots(&ctx, &placements, class);
+                let [room, period] = match slots.choose(&mut rng) {
+                    Some(&slot) => slot,
+                    None => continue,
+                };
+                let mut placed = placements.clone();
+                placed.place(class, room, period);
+                for constraint in CONSTRAINTS.iter() {
+                    let expected =
+                        constraint.cost(&ctx, &placed) - constraint.cost(&ctx, &placements);
+                    let delta = constraint.delta(&ctx, &placements, class.index, room, period);
+                    assert!(
+                        (delta - expected).abs() < EPSILON,
+                        "{:?} seed {} class {}: delta {} but cost changed by {}",
+                        constraint.kind(),
+                        seed,
+                        class.index,
+                        delta,
+                        expected
+                    );
+                    //置いた後に測っても、自分の配置は数えない
+                    let delta = constraint.delta(&ctx, &placed, class.index, room, period);
+                    assert!(
+                        (delta - expected).abs() < EPSILON,
+                        "{:?} seed {} class {}: delta after placing {} but cost changed by {}",
+                        constraint.kind(),
+                        seed,
+                        class.index,
+                        delta,
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn breakdown_follows_placements() {
+        let input = sample::input();
+        let parameters = parameters(&input);
+        let ctx = ConstraintContext::new(&input, &parameters);
+        for seed in 0..20 {
+            let mut rng = SolverRng::seed_from_u64(seed);
+            let mut placements = ctx.new_placements();
+            let mut res = breakdown(&ctx, &placements);
+            for class in ctx.classes.iter() {
+                let slots = free_slots(&ctx, &placements, class);
+                if let Some(&[room, period]) = slots.choose(&mut rng) {
+                    res.add_placement(&ctx, &placements, class.index, room, period);
+                    placements.place(class, room, period);
+                }
+            }
+            for class in ctx.classes.iter().step_by(3) {
+                if let Some([room, period]) = placements.get(class.index) {
+                    placements.remove(class);
+                    res.remove_placement(&ctx, &placements, class.index, room, period);
+                }
+            }
+            let expected = breakdown(&ctx, &placements);
+            for (cost, expected) in res.costs.iter().zip(expected.costs.iter()) {
+                assert!((cost - expected).abs() < EPSILON, "seed {}", seed);
+            }
+        }
+    }
+}
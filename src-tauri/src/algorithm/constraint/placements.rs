@@ -0,0 +1,126 @@
+use crate::input::class::Class;
+use std::collections::BTreeMap;
+
+//授業の配置と、制約の計算に使う占有状況をまとめて持つ
+#[derive(Clone, Debug)]
+pub struct Placements {
+    //slots[class_index] = Some([room, 開始period])
+    slots: Vec<Option<[usize; 2]>>,
+    //occupied[room][period] = class_index
+    occupied: Vec<Vec<Option<usize>>>,
+    //teacher_rooms[teacher_id][period] = [room_id, room_id, ...]
+    teacher_rooms: Vec<BTreeMap<usize, Vec<usize>>>,
+    //student_rooms[student_group_id][period] = [room_id, room_id, ...]
+    student_rooms: Vec<BTreeMap<usize, Vec<usize>>>,
+}
+
+fn add_room(times: &mut BTreeMap<usize, Vec<usize>>, period: usize, room: usize) {
+    times.entry(period).or_insert_with(Vec::new).push(room);
+}
+
+fn remove_room(times: &mut BTreeMap<usize, Vec<usize>>, period: usize, room: usize) {
+    if let Some(rooms) = times.get_mut(&period) {
+        if let Some(position) = rooms.iter().position(|&x| x == room) {
+            rooms.remove(position);
+        }
+        if rooms.is_empty() {
+            times.remove(&period);
+        }
+    }
+}
+
+impl Placements {
+    pub fn new(
+        num_of_classes: usize,
+        num_of_rooms: usize,
+        num_of_periods: usize,
+        num_of_teachers: usize,
+        num_of_students: usize,
+    ) -> Placements {
+        Placements {
+            slots: vec![None; num_of_classes],
+            occupied: vec![vec![None; num_of_periods]; num_of_rooms],
+            teacher_rooms: vec![BTreeMap::new(); num_of_teachers],
+            student_rooms: vec![BTreeMap::new(); num_of_students],
+        }
+    }
+
+    pub fn num_of_periods(&self) -> usize {
+        self.occupied.first().map(|x| x.len()).unwrap_or(0)
+    }
+
+    //時限数を超える部分は切り捨てる
+    pub fn span(&self, class: &Class, period: usize) -> std::ops::Range<usize> {
+        period..(period + class.serial_size).min(self.num_of_periods())
+    }
+
+    pub fn place(&mut self, class: &Class, room: usize, period: usize) {
+        self.slots[class.index] = Some([room, period]);
+        for time in self.span(class, period) {
+            self.occupied[room][time] = Some(class.index);
+            for &teacher in class.teacher_indexes.iter() {
+                add_room(&mut self.teacher_rooms[teacher], time, room);
+            }
+            for &student in class.students_group_indexes.iter() {
+                add_room(&mut self.student_rooms[student], time, room);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, class: &Class) {
+        if let Some([room, period]) = self.slots[class.index].take() {
+            for time in self.span(class, period) {
+                if self.occupied[room][time] == Some(class.index) {
+                    self.occupied[room][time] = None;
+                }
+                for &teacher in class.teacher_indexes.iter() {
+                    remove_room(&mut self.teacher_rooms[teacher], time, room);
+                }
+                for &student in class.students_group_indexes.iter() {
+                    remove_room(&mut self.student_rooms[student], time, room);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, class_index: usize) -> Option<[usize; 2]> {
+        self.slots[class_index]
+    }
+
+    pub fn is_placed(&self, class_index: usize) -> bool {
+        self.slots[class_index].is_some()
+    }
+
+    //置かれていない授業の数
+    pub fn num_of_unplaced(&self) -> usize {
+        self.slots.iter().filter(|x| x.is_none()).count()
+    }
+
+    pub fn get_slots(&self) -> &Vec<Option<[usize; 2]>> {
+        &self.slots
+    }
+
+    pub fn occupant(&self, room: usize, period: usize) -> Option<usize> {
+        self.occupied[room][period]
+    }
+
+    pub fn is_occupied(&self, room: usize, period: usize) -> bool {
+        self.occupied[room][period].is_some()
+    }
+
+    //periodから始まるserial_size時限がすべて空いているか
+    pub fn is_free(&self, room: usize, period: usize, serial_size: usize) -> bool {
+        if period + serial_size > self.num_of_periods() {
+            return false;
+        }
+        (period..period + serial_size).all(|time| !self.is_occupied(room, time))
+    }
+
+    pub fn get_teacher_rooms(&self) -> &Vec<BTreeMap<usize, Vec<usize>>> {
+        &self.teacher_rooms
+    }
+
+    pub fn get_student_rooms(&self) -> &Vec<BTreeMap<usize, Vec<usize>>> {
+        &self.student_rooms
+    }
+}
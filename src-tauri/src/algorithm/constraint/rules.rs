@@ -0,0 +1,554 @@
+use super::{Constraint, ConstraintContext, ConstraintKind, Placements};
+use crate::algorithm::aco::violations::Violations;
+use crate::input::class::Class;
+use std::collections::BTreeMap;
+
+//教室の定員超過
+pub struct Capacity;
+
+fn is_over_capacity(ctx: &ConstraintContext, class: &Class, room: usize) -> bool {
+    class.num_of_students > ctx.rooms[room].capacity
+}
+
+impl Constraint for Capacity {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Capacity
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        let mut res = 0.0;
+        for class in ctx.classes.iter() {
+            if let Some([room, _]) = placements.get(class.index) {
+                if is_over_capacity(ctx, class, room) {
+                    res += ctx.weights.capacity;
+                }
+            }
+        }
+        res
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        _placements: &Placements,
+        class_index: usize,
+        room: usize,
+        _period: usize,
+    ) -> f64 {
+        if is_over_capacity(ctx, &ctx.classes[class_index], room) {
+            return ctx.weights.capacity;
+        }
+        0.0
+    }
+
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations> {
+        match placements.get(class_index) {
+            Some([room, period]) if is_over_capacity(ctx, &ctx.classes[class_index], room) => {
+                vec![Violations::new(period, vec![room])]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+//同じ時限に同じ人が複数の教室にいる。教員と学生グループで共通
+fn conflict_cost(rooms_each_person: &Vec<BTreeMap<usize, Vec<usize>>>, weight: f64) -> f64 {
+    let mut res = 0.0;
+    for times in rooms_each_person.iter() {
+        for rooms in times.values() {
+            let count = rooms.len() as f64;
+            res += count * (count - 1.0) / 2.0 * weight;
+        }
+    }
+    res
+}
+
+//classがすでに置かれていれば、その分は相手に数えない
+fn conflict_delta(
+    rooms_each_person: &Vec<BTreeMap<usize, Vec<usize>>>,
+    people: &Vec<usize>,
+    placements: &Placements,
+    class: &Class,
+    period: usize,
+    weight: f64,
+) -> f64 {
+    let own_span = placements
+        .get(class.index)
+        .map(|[_, own_period]| placements.span(class, own_period));
+    let mut res = 0.0;
+    for &person in people.iter() {
+        for time in placements.span(class, period) {
+            if let Some(rooms) = rooms_each_person[person].get(&time) {
+                let mut count = rooms.len();
+                if own_span.as_ref().is_some_and(|x| x.contains(&time)) {
+                    count -= 1;
+                }
+                res += count as f64 * weight;
+            }
+        }
+    }
+    res
+}
+
+fn conflict_violations(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+    people: fn(&Class) -> &Vec<usize>,
+) -> Vec<Violations> {
+    let mut res = Vec::new();
+    let class = &ctx.classes[class_index];
+    if let Some([_, period]) = placements.get(class_index) {
+        for time in placements.span(class, period) {
+            for room in 0..ctx.rooms.len() {
+                if let Some(other) = placements.occupant(room, time) {
+                    if other == class_index {
+                        continue;
+                    }
+                    if people(&ctx.classes[other])
+                        .iter()
+                        .any(|x| people(class).contains(x))
+                    {
+                        res.push(Violations::new(time, vec![room]));
+                    }
+                }
+            }
+        }
+    }
+    res
+}
+
+pub struct SameTeacher;
+
+impl Constraint for SameTeacher {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::SameTeacher
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        conflict_cost(placements.get_teacher_rooms(), ctx.weights.teacher)
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        _room: usize,
+        period: usize,
+    ) -> f64 {
+        let class = &ctx.classes[class_index];
+        conflict_delta(
+            placements.get_teacher_rooms(),
+            &class.teacher_indexes,
+            placements,
+            class,
+            period,
+            ctx.weights.teacher,
+        )
+    }
+
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations> {
+        conflict_violations(ctx, placements, class_index, |x| &x.teacher_indexes)
+    }
+}
+
+pub struct SameStudentGroup;
+
+impl Constraint for SameStudentGroup {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::SameStudentGroup
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        conflict_cost(placements.get_student_rooms(), ctx.weights.student)
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        _room: usize,
+        period: usize,
+    ) -> f64 {
+        let class = &ctx.classes[class_index];
+        conflict_delta(
+            placements.get_student_rooms(),
+            &class.students_group_indexes,
+            placements,
+            class,
+            period,
+            ctx.weights.student,
+        )
+    }
+
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations> {
+        conflict_violations(ctx, placements, class_index, |x| &x.students_group_indexes)
+    }
+}
+
+//担当教員の不在時限に入っている
+pub struct AbsentDays;
+
+fn absent_periods(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class: &Class,
+    period: usize,
+) -> Vec<usize> {
+    placements
+        .span(class, period)
+        .filter(|x| {
+            class
+                .teacher_indexes
+                .iter()
+                .any(|&teacher| ctx.teachers[teacher].absent_days.contains(x))
+        })
+        .collect()
+}
+
+impl Constraint for AbsentDays {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::AbsentDays
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        let mut res = 0.0;
+        for class in ctx.classes.iter() {
+            if let Some([room, period]) = placements.get(class.index) {
+                res += self.delta(ctx, placements, class.index, room, period);
+            }
+        }
+        res
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        _room: usize,
+        period: usize,
+    ) -> f64 {
+        if !absent_periods(ctx, placements, &ctx.classes[class_index], period).is_empty() {
+            return ctx.weights.absent_days;
+        }
+        0.0
+    }
+
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations> {
+        match placements.get(class_index) {
+            Some([room, period]) => {
+                absent_periods(ctx, placements, &ctx.classes[class_index], period)
+                    .into_iter()
+                    .map(|x| Violations::new(x, vec![room]))
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+//連続授業が日をまたぐ
+pub struct StraddleDays;
+
+fn straddles_days(ctx: &ConstraintContext, class: &Class, period: usize) -> bool {
+    !ctx.calendar.fits_in_day(period, class.serial_size)
+}
+
+impl Constraint for StraddleDays {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::StraddleDays
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        let mut res = 0.0;
+        for class in ctx.classes.iter() {
+            if let Some([_, period]) = placements.get(class.index) {
+                if straddles_days(ctx, class, period) {
+                    res += ctx.weights.straddle_days;
+                }
+            }
+        }
+        res
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        _placements: &Placements,
+        class_index: usize,
+        _room: usize,
+        period: usize,
+    ) -> f64 {
+        if straddles_days(ctx, &ctx.classes[class_index], period) {
+            return ctx.weights.straddle_days;
+        }
+        0.0
+    }
+
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations> {
+        match placements.get(class_index) {
+            Some([room, period]) if straddles_days(ctx, &ctx.classes[class_index], period) => {
+                vec![Violations::new(period, vec![room])]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+//使われている枠(size_of_frame時限ごと)の空き時限。授業をなるべく固めるための項
+pub struct Collection;
+
+fn frame_range(ctx: &ConstraintContext, num_of_periods: usize, frame: usize) -> (usize, usize) {
+    let start = frame * ctx.size_of_frame;
+    (start, (start + ctx.size_of_frame).min(num_of_periods))
+}
+
+fn frame_empty_cost(length: usize, count: usize) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    (length - count) as f64
+}
+
+impl Constraint for Collection {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Collection
+    }
+
+    fn is_hard(&self) -> bool {
+        false
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        let num_of_periods = placements.num_of_periods();
+        //時限数が枠の大きさで割り切れない場合、最後の枠は短くなる
+        let num_of_frames = (num_of_periods + ctx.size_of_frame - 1) / ctx.size_of_frame;
+        let mut res = 0.0;
+        for room in 0..ctx.rooms.len() {
+            for frame in 0..num_of_frames {
+                let (start, end) = frame_range(ctx, num_of_periods, frame);
+                let count = (start..end)
+                    .filter(|&x| placements.is_occupied(room, x))
+                    .count();
+                res += frame_empty_cost(end - start, count) * ctx.weights.collection;
+            }
+        }
+        res
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+        room: usize,
+        period: usize,
+    ) -> f64 {
+        let num_of_periods = placements.num_of_periods();
+        let span = placements.span(&ctx.classes[class_index], period);
+        if span.is_empty() {
+            return 0.0;
+        }
+        //class自身が置かれている時限は空きとして数える
+        let is_occupied = |x: usize| {
+            placements
+                .occupant(room, x)
+                .is_some_and(|other| other != class_index)
+        };
+        let mut res = 0.0;
+        for frame in span.start / ctx.size_of_frame..=(span.end - 1) / ctx.size_of_frame {
+            let (start, end) = frame_range(ctx, num_of_periods, frame);
+            let count = (start..end).filter(|&x| is_occupied(x)).count();
+            let added = (start..end)
+                .filter(|&x| span.contains(&x) && !is_occupied(x))
+                .count();
+            res += (frame_empty_cost(end - start, count + added)
+                - frame_empty_cost(end - start, count))
+                * ctx.weights.collection;
+        }
+        res
+    }
+
+    //空き時限が埋まるたびに1ずつ減るので、連続数の分だけ負になりうる
+    fn min_delta(&self, ctx: &ConstraintContext, class_index: usize) -> f64 {
+        -(ctx.classes[class_index].serial_size as f64) * ctx.weights.collection
+    }
+
+    //個々のセルの違反ではないので表示しない
+    fn violations(
+        &self,
+        _ctx: &ConstraintContext,
+        _placements: &Placements,
+        _class_index: usize,
+    ) -> Vec<Violations> {
+        Vec::new()
+    }
+}
+
+//候補にない教室に置かれている。ACOは候補からしか選ばないので手動の配置でだけ起きる
+pub struct RoomNotCandidate;
+
+fn is_not_candidate(class: &Class, room: usize) -> bool {
+    !class.room_candidates_indexes.contains(&room)
+}
+
+impl Constraint for RoomNotCandidate {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::RoomNotCandidate
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        let mut res = 0.0;
+        for class in ctx.classes.iter() {
+            if let Some([room, _]) = placements.get(class.index) {
+                if is_not_candidate(class, room) {
+                    res += ctx.weights.room_not_candidate;
+                }
+            }
+        }
+        res
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        _placements: &Placements,
+        class_index: usize,
+        room: usize,
+        _period: usize,
+    ) -> f64 {
+        if is_not_candidate(&ctx.classes[class_index], room) {
+            return ctx.weights.room_not_candidate;
+        }
+        0.0
+    }
+
+    fn violations(
+        &self,
+        ctx: &ConstraintContext,
+        placements: &Placements,
+        class_index: usize,
+    ) -> Vec<Violations> {
+        match placements.get(class_index) {
+            Some([room, period]) if is_not_candidate(&ctx.classes[class_index], room) => {
+                vec![Violations::new(period, vec![room])]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+//置ける場所がなく置かれなかった授業。置くたびに1件ずつ減る
+pub struct Unassigned;
+
+impl Constraint for Unassigned {
+    fn kind(&self) -> ConstraintKind {
+        ConstraintKind::Unassigned
+    }
+
+    fn is_hard(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn cost(&self, ctx: &ConstraintContext, placements: &Placements) -> f64 {
+        placements.num_of_unplaced() as f64 * ctx.weights.unassigned
+    }
+
+    fn delta(
+        &self,
+        ctx: &ConstraintContext,
+        _placements: &Placements,
+        _class_index: usize,
+        _room: usize,
+        _period: usize,
+    ) -> f64 {
+        -ctx.weights.unassigned
+    }
+
+    //どこに置いても同じだけ減るので、場所の比べ方には影響しない
+    fn min_delta(&self, ctx: &ConstraintContext, _class_index: usize) -> f64 {
+        -ctx.weights.unassigned
+    }
+
+    //セルがないので表示しない。時間割ではunscheduledに並ぶ
+    fn violations(
+        &self,
+        _ctx: &ConstraintContext,
+        _placements: &Placements,
+        _class_index: usize,
+    ) -> Vec<Violations> {
+        Vec::new()
+    }
+}
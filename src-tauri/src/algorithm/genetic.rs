@@ -0,0 +1,322 @@
+use super::aco::aco_parameters::{check_f64, check_usize, AcoParameters, ParameterError};
+use super::aco::aco_solver::{new_seed, SolverRng};
+use super::constraint::{self, ConstraintContext, Placements, Score, ScoreBreakdown};
+use super::solver::{self, Solver, SolverKind};
+use crate::input::Input;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GeneticSettings {
+    pub population_size: usize,
+    pub tournament_size: usize,
+    //交叉せずに親を写す確率は1 - crossover_rate
+    pub crossover_rate: f64,
+    //授業ごとに置き直す確率
+    pub mutation_rate: f64,
+    //そのまま次の世代に残す上位の数
+    pub elite_count: usize,
+}
+
+impl Default for GeneticSettings {
+    fn default() -> Self {
+        GeneticSettings {
+            population_size: 20,
+            tournament_size: 3,
+            crossover_rate: 0.9,
+            mutation_rate: 0.02,
+            elite_count: 2,
+        }
+    }
+}
+
+impl GeneticSettings {
+    pub fn validate(&self, errors: &mut Vec<ParameterError>) {
+        check_usize(
+            errors,
+            "genetic.populationSize",
+            self.population_size,
+            2,
+            10000,
+        );
+        check_usize(
+            errors,
+            "genetic.tournamentSize",
+            self.tournament_size,
+            1,
+            self.population_size.max(1),
+        );
+        check_f64(
+            errors,
+            "genetic.crossoverRate",
+            self.crossover_rate,
+            0.0,
+            1.0,
+        );
+        check_f64(errors, "genetic.mutationRate", self.mutation_rate, 0.0, 1.0);
+        check_usize(
+            errors,
+            "genetic.eliteCount",
+            self.elite_count,
+            0,
+            self.population_size.saturating_sub(1),
+        );
+    }
+}
+
+#[derive(Clone)]
+struct Individual {
+    placements: Placements,
+    breakdown: ScoreBreakdown,
+}
+
+impl Individual {
+    fn new(ctx: &ConstraintContext, placements: Placements) -> Individual {
+        Individual {
+            breakdown: constraint::breakdown(ctx, &placements),
+            placements,
+        }
+    }
+
+    fn score(&self) -> Score {
+        self.breakdown.score()
+    }
+}
+
+//遺伝的アルゴリズム。個体は授業ごとの場所で、重なる場所は置き直して修復する
+#[derive(Clone)]
+pub struct GeneticSolver {
+    parameters: AcoParameters,
+    input: Input,
+    locks: Vec<Option<(usize, usize)>>,
+    //良い順に並べる
+    population: Vec<Individual>,
+    best: Option<Individual>,
+    seed: u64,
+    rng: SolverRng,
+}
+
+fn sort_population(population: &mut Vec<Individual>) {
+    population.sort_by(|a, b| {
+        let (a, b) = (a.score(), b.score());
+        a.hard.total_cmp(&b.hard).then(a.soft.total_cmp(&b.soft))
+    });
+}
+
+impl GeneticSolver {
+    pub fn new(mut parameters: AcoParameters, input: Input) -> GeneticSolver {
+        let seed = parameters.seed.unwrap_or_else(new_seed);
+        parameters.seed = Some(seed);
+        let num_of_classes = input.get_classes().len();
+        GeneticSolver {
+            parameters,
+            input,
+            locks: vec![None; num_of_classes],
+            population: Vec::new(),
+            best: None,
+            seed,
+            rng: SolverRng::seed_from_u64(seed),
+        }
+    }
+}
+
+//良い順に並んでいるので、添字が小さい方をトーナメントの勝者とする
+fn select(population: &Vec<Individual>, tournament_size: usize, rng: &mut SolverRng) -> usize {
+    let mut res = rng.gen_range(0..population.len());
+    for _ in 1..tournament_size {
+        res = res.min(rng.gen_range(0..population.len()));
+    }
+    res
+}
+
+//授業ごとにどちらかの親の場所を選ぶ。空いていなければもう一方、それもだめなら無作為に置く
+fn crossover(
+    ctx: &ConstraintContext,
+    locks: &Vec<Option<(usize, usize)>>,
+    a: &Placements,
+    b: &Placements,
+    rng: &mut SolverRng,
+) -> Placements {
+    let mut child = ctx.new_placements();
+    for (class_index, lock) in locks.iter().enumerate() {
+        if let Some((room, period)) = lock {
+            child.place(&ctx.classes[class_index], *room, *period);
+        }
+    }
+    let mut order = (0..ctx.classes.len()).collect::<Vec<usize>>();
+    order.shuffle(rng);
+    for class_index in order {
+        if child.is_placed(class_index) {
+            continue;
+        }
+        let class = &ctx.classes[class_index];
+        let mut parents = [a.get(class_index), b.get(class_index)];
+        if rng.gen::<bool>() {
+            parents.swap(0, 1);
+        }
+        let slot = parents
+            .iter()
+            .flatten()
+            .find(|[room, period]| child.is_free(*room, *period, class.serial_size));
+        match slot {
+            Some(&[room, period]) => child.place(class, room, period),
+            None => {
+                solver::place_randomly(ctx, &mut child, class_index, rng);
+            }
+        }
+    }
+    child
+}
+
+fn mutate(
+    ctx: &ConstraintContext,
+    locks: &Vec<Option<(usize, usize)>>,
+    mutation_rate: f64,
+    placements: &mut Placements,
+    rng: &mut SolverRng,
+) {
+    for class_index in 0..ctx.classes.len() {
+        if locks[class_index].is_some() || rng.gen::<f64>() >= mutation_rate {
+            continue;
+        }
+        let class = &ctx.classes[class_index];
+        let from = placements.get(class_index);
+        placements.remove(class);
+        if !solver::place_randomly(ctx, placements, class_index, rng) {
+            if let Some([room, period]) = from {
+                placements.place(class, room, period);
+            }
+        }
+    }
+}
+
+impl Solver for GeneticSolver {
+    fn kind(&self) -> SolverKind {
+        SolverKind::Genetic
+    }
+
+    fn initialize(&mut self) {
+        self.rng = SolverRng::seed_from_u64(self.seed);
+        let ctx = ConstraintContext::new(&self.input, &self.parameters);
+        let mut population = Vec::new();
+        for _ in 0..self.parameters.genetic.population_size {
+            let placements = solver::random_placements(&ctx, &self.locks, &mut self.rng);
+            population.push(Individual::new(&ctx, placements));
+        }
+        sort_population(&mut population);
+        self.best = population.first().cloned();
+        self.population = population;
+    }
+
+    fn step(&mut self) {
+        if self.population.is_empty() {
+            self.initialize();
+        }
+        let ctx = ConstraintContext::new(&self.input, &self.parameters);
+        let settings = &self.parameters.genetic;
+        let elite_count = settings.elite_count.min(self.population.len());
+        let mut next = self.population[..elite_count].to_vec();
+        while next.len() < settings.population_size {
+            let a = select(&self.population, settings.tournament_size, &mut self.rng);
+            let b = select(&self.population, settings.tournament_size, &mut self.rng);
+            let mut placements = if self.rng.gen::<f64>() < settings.crossover_rate {
+                crossover(
+                    &ctx,
+                    &self.locks,
+                    &self.population[a].placements,
+                    &self.population[b].placements,
+                    &mut self.rng,
+                )
+            } else {
+                self.population[a].placements.clone()
+            };
+            mutate(
+                &ctx,
+                &self.locks,
+                settings.mutation_rate,
+                &mut placements,
+                &mut self.rng,
+            );
+            next.push(Individual::new(&ctx, placements));
+        }
+        sort_population(&mut next);
+        let is_improved = match &self.best {
+            Some(best) => next[0].score().is_better_than(&best.score()),
+            None => true,
+        };
+        if is_improved {
+            self.best = Some(next[0].clone());
+        }
+        self.population = next;
+    }
+
+    fn best_solution(&self) -> Option<&Placements> {
+        self.best.as_ref().map(|x| &x.placements)
+    }
+
+    fn breakdown(&self) -> ScoreBreakdown {
+        self.best
+            .as_ref()
+            .map_or(ScoreBreakdown::default(), |x| x.breakdown)
+    }
+
+    fn current_score(&self) -> Score {
+        self.population
+            .first()
+            .map_or(Score::default(), |x| x.score())
+    }
+
+    //最後は最も良かった個体を集団の先頭に戻して、エリートが0でも残るようにする
+    fn finish_run(&mut self) {
+        if let Some(best) = &self.best {
+            if self.population.is_empty() {
+                self.population.push(best.clone());
+            } else {
+                self.population[0] = best.clone();
+            }
+        }
+    }
+
+    //固定が変わったら今の集団と最良解に反映する。作り直さないので乱数も最良解も続きから使う
+    fn set_locks(&mut self, locks: &Vec<Option<(usize, usize)>>) {
+        let mut is_changed = false;
+        for (class_index, lock) in locks.iter().enumerate().take(self.locks.len()) {
+            if self.locks[class_index] != *lock {
+                self.locks[class_index] = *lock;
+                is_changed = true;
+            }
+        }
+        if !is_changed {
+            return;
+        }
+        let ctx = ConstraintContext::new(&self.input, &self.parameters);
+        for individual in self.population.iter_mut().chain(self.best.iter_mut()) {
+            solver::apply_locks(&ctx, &mut individual.placements, &self.locks, &mut self.rng);
+            individual.breakdown = constraint::breakdown(&ctx, &individual.placements);
+        }
+        sort_population(&mut self.population);
+        if let (Some(first), Some(best)) = (self.population.first(), &self.best) {
+            if first.score().is_better_than(&best.score()) {
+                self.best = Some(first.clone());
+            }
+        }
+    }
+
+    fn get_locks(&self) -> &Vec<Option<(usize, usize)>> {
+        &self.locks
+    }
+
+    fn get_parameters(&self) -> &AcoParameters {
+        &self.parameters
+    }
+
+    fn get_input(&self) -> &Input {
+        &self.input
+    }
+
+    fn get_seed(&self) -> u64 {
+        self.seed
+    }
+}
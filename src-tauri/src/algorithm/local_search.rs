@@ -0,0 +1,368 @@
+use super::constraint::{self, ConstraintContext, Placements, Score, ScoreBreakdown};
+use serde::{Deserialize, Serialize};
+
+//浮動小数の誤差で改善と判定しないための幅
+const EPSILON: f64 = 1e-9;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalSearchMode {
+    Off,
+    //毎世代のbest_antに掛ける
+    EachIteration,
+    //実行の最後に1回だけ掛ける
+    EndOfRun,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalSearchSettings {
+    pub mode: LocalSearchMode,
+    //1回の探索で採用する移動の上限
+    pub max_steps: usize,
+}
+
+impl Default for LocalSearchSettings {
+    fn default() -> Self {
+        LocalSearchSettings {
+            mode: LocalSearchMode::Off,
+            max_steps: 100,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalSearchResult {
+    pub steps: usize,
+    pub before: Score,
+    pub after: Score,
+}
+
+//1つの授業の動かし方。他の探索法もこの近傍を使う
+pub enum Move {
+    Relocate { class_index: usize, to: [usize; 2] },
+    Swap { class_a: usize, class_b: usize },
+    //置かれていない授業を空いている場所に置く
+    Insert { class_index: usize, to: [usize; 2] },
+}
+
+pub fn is_improvement(new: &Score, old: &Score) -> bool {
+    if (new.hard - old.hard).abs() > EPSILON {
+        return new.hard < old.hard;
+    }
+    new.soft < old.soft - EPSILON
+}
+
+//classを外した状態から(room, period)に置いたときの増分
+fn delta(
+    ctx: &ConstraintContext,
+    placements: &Placements,
+    class_index: usize,
+    to: [usize; 2],
+) -> Score {
+    constraint::slot_delta(ctx, placements, class_index, to[0], to[1])
+}
+
+//空いている候補の場所へ1つ動かす
+fn find_relocation(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    class_index: usize,
+) -> Option<Move> {
+    let class = &ctx.classes[class_index];
+    let from = placements.get(class_index)?;
+    placements.remove(class);
+    let current = delta(ctx, placements, class_index, from);
+    let mut res = None;
+    let mut best = current;
+    for &room in class.room_candidates_indexes.iter() {
+        for period in 0..placements.num_of_periods() {
+            if [room, period] == from || !placements.is_free(room, period, class.serial_size) {
+                continue;
+            }
+            let score = delta(ctx, placements, class_index, [room, period]);
+            if is_improvement(&score, &best) {
+                best = score;
+                res = Some(Move::Relocate {
+                    class_index,
+                    to: [room, period],
+                });
+            }
+        }
+    }
+    placements.place(class, from[0], from[1]);
+    res
+}
+
+//移動前と移動後の増分を返す。置けない移動ならNone。placementsは元に戻す
+pub fn move_delta(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    next: &Move,
+) -> Option<(Score, Score)> {
+    match *next {
+        Move::Relocate { class_index, to } => {
+            let class = &ctx.classes[class_index];
+            let from = placements.get(class_index)?;
+            if from == to || !class.room_candidates_indexes.contains(&to[0]) {
+                return None;
+            }
+            placements.remove(class);
+            let mut res = None;
+            if placements.is_free(to[0], to[1], class.serial_size) {
+                res = Some((
+                    delta(ctx, placements, class_index, from),
+                    delta(ctx, placements, class_index, to),
+                ));
+            }
+            placements.place(class, from[0], from[1]);
+            res
+        }
+        Move::Swap { class_a, class_b } => {
+            let (a, b) = (&ctx.classes[class_a], &ctx.classes[class_b]);
+            let slot_a = placements.get(class_a)?;
+            let slot_b = placements.get(class_b)?;
+            if class_a == class_b || slot_a == slot_b || a.serial_size != b.serial_size {
+                return None;
+            }
+            if !a.room_candidates_indexes.contains(&slot_b[0])
+                || !b.room_candidates_indexes.contains(&slot_a[0])
+            {
+                return None;
+            }
+            placements.remove(a);
+            placements.remove(b);
+            let old_a = delta(ctx, placements, class_a, slot_a);
+            placements.place(a, slot_a[0], slot_a[1]);
+            let old = old_a + delta(ctx, placements, class_b, slot_b);
+            placements.remove(a);
+            let new_a = delta(ctx, placements, class_a, slot_b);
+            placements.place(a, slot_b[0], slot_b[1]);
+            let new = new_a + delta(ctx, placements, class_b, slot_a);
+            placements.remove(a);
+            placements.place(a, slot_a[0], slot_a[1]);
+            placements.place(b, slot_b[0], slot_b[1]);
+            Some((old, new))
+        }
+        Move::Insert { class_index, to } => {
+            let class = &ctx.classes[class_index];
+            if placements.is_placed(class_index)
+                || !class.room_candidates_indexes.contains(&to[0])
+                || !placements.is_free(to[0], to[1], class.serial_size)
+            {
+                return None;
+            }
+            //置く前のコストはUnassignedに入っているので、置いた増分だけを比べる
+            Some((Score::default(), delta(ctx, placements, class_index, to)))
+        }
+    }
+}
+
+//同じ長さの授業と場所を入れ替える
+fn find_swap(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    class_a: usize,
+    locked: &Vec<bool>,
+) -> Option<Move> {
+    for class_b in (class_a + 1)..ctx.classes.len() {
+        if locked[class_b] {
+            continue;
+        }
+        let next = Move::Swap { class_a, class_b };
+        if let Some((old, new)) = move_delta(ctx, placements, &next) {
+            if is_improvement(&new, &old) {
+                return Some(next);
+            }
+        }
+    }
+    None
+}
+
+//移動を行い、breakdownに増分を足す
+pub fn apply(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    breakdown: &mut ScoreBreakdown,
+    next: Move,
+) {
+    match next {
+        Move::Relocate { class_index, to } => {
+            remove(ctx, placements, breakdown, class_index);
+            place(ctx, placements, breakdown, class_index, to);
+        }
+        Move::Swap { class_a, class_b } => {
+            let slot_a = placements.get(class_a).unwrap();
+            let slot_b = placements.get(class_b).unwrap();
+            remove(ctx, placements, breakdown, class_a);
+            remove(ctx, placements, breakdown, class_b);
+            place(ctx, placements, breakdown, class_a, slot_b);
+            place(ctx, placements, breakdown, class_b, slot_a);
+        }
+        Move::Insert { class_index, to } => {
+            place(ctx, placements, breakdown, class_index, to);
+        }
+    }
+}
+
+fn remove(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    breakdown: &mut ScoreBreakdown,
+    class_index: usize,
+) {
+    if let Some([room, period]) = placements.get(class_index) {
+        placements.remove(&ctx.classes[class_index]);
+        breakdown.remove_placement(ctx, placements, class_index, room, period);
+    }
+}
+
+fn place(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    breakdown: &mut ScoreBreakdown,
+    class_index: usize,
+    to: [usize; 2],
+) {
+    breakdown.add_placement(ctx, placements, class_index, to[0], to[1]);
+    placements.place(&ctx.classes[class_index], to[0], to[1]);
+}
+
+//移動と入れ替えで改善が見つかる限り進める山登り。固定された授業は動かさない
+//breakdownはplacementsのコストで、動かすたびに増分だけ足す
+pub fn hill_climb(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    breakdown: &mut ScoreBreakdown,
+    locked: &Vec<bool>,
+    max_steps: usize,
+) -> LocalSearchResult {
+    let before = breakdown.score();
+    let mut steps = 0;
+    while steps < max_steps {
+        let mut next = None;
+        for class_index in 0..ctx.classes.len() {
+            if locked[class_index] {
+                continue;
+            }
+            next = find_relocation(ctx, placements, class_index)
+                .or_else(|| find_swap(ctx, placements, class_index, locked));
+            if next.is_some() {
+                break;
+            }
+        }
+        match next {
+            Some(next) => apply(ctx, placements, breakdown, next),
+            None => break,
+        }
+        steps += 1;
+    }
+    LocalSearchResult {
+        steps,
+        before,
+        after: breakdown.score(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::aco::aco_parameters::{AcoParameters, AcoSettings};
+    use crate::algorithm::aco::aco_solver::SolverRng;
+    use crate::input::sample;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    //候補の教室の空いている場所に無作為に置く。置かない授業も残す
+    fn random_placements(ctx: &ConstraintContext, rng: &mut SolverRng) -> Placements {
+        let mut placements = ctx.new_placements();
+        for class in ctx.classes.iter() {
+            if rng.gen::<f64>() < 0.2 {
+                continue;
+            }
+            let room = *class.room_candidates_indexes.choose(rng).unwrap();
+            let period = rng.gen_range(0..placements.num_of_periods());
+            if placements.is_free(room, period, class.serial_size) {
+                placements.place(class, room, period);
+            }
+        }
+        placements
+    }
+
+    fn random_move(ctx: &ConstraintContext, placements: &Placements, rng: &mut SolverRng) -> Move {
+        let class_index = rng.gen_range(0..ctx.classes.len());
+        let room = *ctx.classes[class_index]
+            .room_candidates_indexes
+            .choose(rng)
+            .unwrap();
+        let to = [room, rng.gen_range(0..placements.num_of_periods())];
+        match rng.gen_range(0..3) {
+            0 => Move::Relocate { class_index, to },
+            1 => Move::Swap {
+                class_a: class_index,
+                class_b: rng.gen_range(0..ctx.classes.len()),
+            },
+            _ => Move::Insert { class_index, to },
+        }
+    }
+
+    fn assert_close(actual: Score, expected: Score, message: &str) {
+        assert!(
+            (actual.hard - expected.hard).abs() < EPSILON
+                && (actual.soft - expected.soft).abs() < EPSILON,
+            "{}: {:?} but expected {:?}",
+            message,
+            actual,
+            expected
+        );
+    }
+
+    //move_deltaの差とapplyの増分が、どちらも数え直したコストと一致する
+    #[test]
+    fn move_delta_matches_recomputed_cost() {
+        let input = sample::input();
+        let parameters = AcoParameters::new(&AcoSettings::default(), &input);
+        let ctx = ConstraintContext::new(&input, &parameters);
+        let mut num_of_checked = 0;
+        for seed in 0..50 {
+            let mut rng = SolverRng::seed_from_u64(seed);
+            let mut placements = random_placements(&ctx, &mut rng);
+            for _ in 0..50 {
+                let next = random_move(&ctx, &placements, &mut rng);
+                let slots = placements.get_slots().clone();
+                let (old, new) = match move_delta(&ctx, &mut placements, &next) {
+                    Some(res) => res,
+                    None => continue,
+                };
+                assert_eq!(
+                    placements.get_slots(),
+                    &slots,
+                    "move_delta must restore placements"
+                );
+                let is_insert = matches!(next, Move::Insert { .. });
+                let before = constraint::breakdown(&ctx, &placements);
+                let mut breakdown = before;
+                let mut moved = placements.clone();
+                apply(&ctx, &mut moved, &mut breakdown, next);
+                let after = constraint::breakdown(&ctx, &moved);
+                assert_close(breakdown.score(), after.score(), "apply");
+                //Insertは置く前のコストをUnassignedで持つので、移動と入れ替えだけ比べる
+                if !is_insert {
+                    let change = Score {
+                        hard: after.score().hard - before.score().hard,
+                        soft: after.score().soft - before.score().soft,
+                    };
+                    let delta = Score {
+                        hard: new.hard - old.hard,
+                        soft: new.soft - old.soft,
+                    };
+                    assert_close(delta, change, "move_delta");
+                    num_of_checked += 1;
+                }
+                placements = moved;
+            }
+        }
+        assert!(num_of_checked > 0);
+    }
+}
@@ -0,0 +1,385 @@
+use super::aco::ordering::ClassOrdering;
+use super::aco::stagnation::Restart;
+use super::constraint::{ConstraintKind, Score};
+use super::solver::{Solver, SolverKind, SolverManager};
+use super::stop_criteria::{RunStatus, StopCriteria, StopReason};
+use super::time_table::{self, TimeTable, TimeTableManager};
+use serde::{Deserialize, Serialize};
+use std::sync::{Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+pub const PROGRESS_EVENT: &str = "solver-progress";
+pub const SNAPSHOT_EVENT: &str = "solver-snapshot";
+pub const FINISHED_EVENT: &str = "solver-finished";
+
+//時間割の途中経過を送る間隔
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RunState {
+    Idle,
+    Running,
+    Paused,
+    Stopping,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SolverProgress {
+    pub state: RunState,
+    pub iteration: usize,
+    pub best_score: f64,
+    pub super_score: f64,
+    //best_solutionのhardとsoftのコスト
+    pub hard_score: f64,
+    pub soft_score: f64,
+    pub same_group_violations: usize,
+    pub same_teacher_violations: usize,
+    pub capacity_violations: usize,
+    pub strabble_days_violations: usize,
+    pub absent_days_violations: usize,
+    //置ける場所がなく時間割に載らなかった授業
+    pub unassigned_classes: usize,
+    pub diversity: Option<f64>,
+    pub restarts: usize,
+}
+
+impl SolverProgress {
+    //評価値は覚えているbreakdownから作る。違反の数は数え直すと重いので前回の値を引き継ぐ
+    fn new(
+        solver: &dyn Solver,
+        state: RunState,
+        iteration: usize,
+        previous: Option<&SolverProgress>,
+    ) -> SolverProgress {
+        let score = solver.score();
+        SolverProgress {
+            state,
+            iteration,
+            best_score: solver.current_score().total(),
+            super_score: score.total(),
+            hard_score: score.hard,
+            soft_score: score.soft,
+            same_group_violations: previous.map_or(0, |x| x.same_group_violations),
+            same_teacher_violations: previous.map_or(0, |x| x.same_teacher_violations),
+            capacity_violations: previous.map_or(0, |x| x.capacity_violations),
+            strabble_days_violations: previous.map_or(0, |x| x.strabble_days_violations),
+            absent_days_violations: previous.map_or(0, |x| x.absent_days_violations),
+            unassigned_classes: solver
+                .best_solution()
+                .map_or(0, |placements| placements.num_of_unplaced()),
+            diversity: solver.diversity(),
+            restarts: solver.restarts().len(),
+        }
+    }
+
+    //時間割の途中経過を送るときだけ違反を数え直す
+    fn count_violations(&mut self, solver: &dyn Solver) {
+        self.same_group_violations = solver.violations(ConstraintKind::SameStudentGroup).len();
+        self.same_teacher_violations = solver.violations(ConstraintKind::SameTeacher).len();
+        self.capacity_violations = solver.violations(ConstraintKind::Capacity).len();
+        self.strabble_days_violations = solver.violations(ConstraintKind::StraddleDays).len();
+        self.absent_days_violations = solver.violations(ConstraintKind::AbsentDays).len();
+    }
+}
+
+//実行の結果。終了イベントで送り、最後の1件を保持する
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunResult {
+    pub stop_reason: StopReason,
+    //同じ入力とパラメータでこのシードを使えば同じ結果になる
+    pub seed: Option<u64>,
+    //順番の戦略ごとに結果を比べられるよう、ACOのときは使った順番を載せる
+    pub class_ordering: Option<ClassOrdering>,
+    pub elapsed_ms: u64,
+    pub progress: Option<SolverProgress>,
+    pub restarts: Vec<Restart>,
+    pub time_table: Option<TimeTable>,
+}
+
+//ソルバーを別スレッドで回す。ロックは1世代ごとに取り直すので、実行中も他のコマンドが通る
+pub struct SolverRunner {
+    state: Mutex<RunState>,
+    resumed: Condvar,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    criteria: Mutex<StopCriteria>,
+    last_result: Mutex<Option<RunResult>>,
+}
+
+impl SolverRunner {
+    pub fn new() -> SolverRunner {
+        SolverRunner {
+            state: Mutex::new(RunState::Idle),
+            resumed: Condvar::new(),
+            worker: Mutex::new(None),
+            criteria: Mutex::new(StopCriteria::default()),
+            last_result: Mutex::new(None),
+        }
+    }
+
+    pub fn get_state(&self) -> RunState {
+        *self.state.lock().unwrap()
+    }
+
+    //実行中は途中経過で時間割が上書きされるので、時間割の編集は止まっているときだけ受け付ける
+    pub fn check_idle(&self) -> Result<(), String> {
+        match self.get_state() {
+            RunState::Idle => Ok(()),
+            state => Err(format!("solver is {:?}", state)),
+        }
+    }
+
+    fn set_state(&self, state: RunState) {
+        *self.state.lock().unwrap() = state;
+        self.resumed.notify_all();
+    }
+
+    //一時停止中はここで待ち、待った時間をpausedに足す。止めるべきならfalseを返す
+    fn wait_while_paused(&self, paused: &mut Duration) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if *state == RunState::Paused {
+            let start = Instant::now();
+            while *state == RunState::Paused {
+                state = self.resumed.wait(state).unwrap();
+            }
+            *paused += start.elapsed();
+        }
+        *state == RunState::Running
+    }
+}
+
+//時間割の保存はソルバーのロックを外してから行う
+fn make_snapshot(solver: &dyn Solver) -> Option<TimeTable> {
+    match time_table::convert_solver_to_timetable(solver) {
+        Ok(res) => Some(res),
+        Err(e) => {
+            println!("failed to make snapshot: {}", e);
+            None
+        }
+    }
+}
+
+fn emit_snapshot(app_handle: &tauri::AppHandle, snapshot: Option<TimeTable>) {
+    if let Some(res) = snapshot {
+        time_table::save_timetable(app_handle.state::<TimeTableManager>(), res.clone());
+        if let Err(e) = app_handle.emit_all(SNAPSHOT_EVENT, res) {
+            println!("failed to emit snapshot: {}", e);
+        }
+    }
+}
+
+fn run_worker(app_handle: tauri::AppHandle) {
+    let runner = app_handle.state::<SolverRunner>();
+    let solver_manager = app_handle.state::<SolverManager>();
+    let criteria = runner.criteria.lock().unwrap().clone();
+    //Startは毎回新しい実行にする。シードから乱数を引き直すので、結果のseedで同じ時間割を作り直せる
+    if let Some(solver) = solver_manager.solver.lock().unwrap().as_mut() {
+        solver.initialize();
+    }
+    //経過時間は送信や保存も含めた実時間から、一時停止していた時間を引く
+    let run_start = Instant::now();
+    let mut paused = Duration::ZERO;
+    let mut elapsed = Duration::ZERO;
+    let mut last_snapshot = Instant::now();
+    let mut iteration = 0;
+    let mut best_score: Option<Score> = None;
+    let mut iterations_without_improvement = 0;
+    let mut last_progress: Option<SolverProgress> = None;
+    let stop_reason = loop {
+        if !runner.wait_while_paused(&mut paused) {
+            break StopReason::Stopped;
+        }
+        let mut snapshot = None;
+        let (progress, super_score, default_max_iterations) = {
+            let mut managed_solver = solver_manager.solver.lock().unwrap();
+            let solver = match managed_solver.as_mut() {
+                Some(solver) => solver,
+                None => break StopReason::NoSolver,
+            };
+            solver.step();
+            iteration += 1;
+            let mut progress = SolverProgress::new(
+                solver.as_ref(),
+                runner.get_state(),
+                iteration,
+                last_progress.as_ref(),
+            );
+            if last_snapshot.elapsed() >= SNAPSHOT_INTERVAL {
+                progress.count_violations(solver.as_ref());
+                snapshot = make_snapshot(solver.as_ref());
+                last_snapshot = Instant::now();
+            } else if last_progress.is_none() {
+                progress.count_violations(solver.as_ref());
+            }
+            (
+                progress,
+                solver.score(),
+                solver.get_parameters().max_iterations,
+            )
+        };
+        if best_score.is_none_or(|x| super_score.is_better_than(&x)) {
+            best_score = Some(super_score);
+            iterations_without_improvement = 0;
+        } else {
+            iterations_without_improvement += 1;
+        }
+        if let Err(e) = app_handle.emit_all(PROGRESS_EVENT, progress.clone()) {
+            println!("failed to emit progress: {}", e);
+        }
+        emit_snapshot(&app_handle, snapshot);
+        elapsed = run_start.elapsed().saturating_sub(paused);
+        let status = RunStatus {
+            iteration,
+            elapsed,
+            //どちらもこれまでで最も良い解で判定する
+            best_score: progress.super_score,
+            //違反の数は間引いて数えるので、hardのコストで判定する
+            is_feasible: progress.hard_score <= 0.0,
+            iterations_without_improvement,
+        };
+        last_progress = Some(progress);
+        if let Some(reason) = criteria.check(&status, default_max_iterations) {
+            break reason;
+        }
+    };
+    let mut class_ordering = None;
+    let (time_table, seed, restarts) = match solver_manager.solver.lock().unwrap().as_mut() {
+        Some(solver) => {
+            if solver.kind() == SolverKind::Aco {
+                class_ordering = Some(solver.get_parameters().class_ordering);
+            }
+            let mut time_table = None;
+            if solver.best_solution().is_some() {
+                solver.finish_run();
+                //最後の局所探索で変わった評価値を結果に反映する
+                let mut progress = SolverProgress::new(
+                    solver.as_ref(),
+                    RunState::Idle,
+                    iteration,
+                    last_progress.as_ref(),
+                );
+                progress.count_violations(solver.as_ref());
+                last_progress = Some(progress);
+                time_table = make_snapshot(solver.as_ref());
+            }
+            (
+                time_table,
+                Some(solver.get_seed()),
+                solver.restarts().to_vec(),
+            )
+        }
+        None => (None, None, Vec::new()),
+    };
+    emit_snapshot(&app_handle, time_table.clone());
+    let result = RunResult {
+        stop_reason,
+        seed,
+        class_ordering,
+        elapsed_ms: elapsed.as_millis() as u64,
+        progress: last_progress.map(|mut x| {
+            x.state = RunState::Idle;
+            x
+        }),
+        restarts,
+        time_table,
+    };
+    *runner.last_result.lock().unwrap() = Some(result.clone());
+    runner.set_state(RunState::Idle);
+    if let Err(e) = app_handle.emit_all(FINISHED_EVENT, result) {
+        println!("failed to emit finished: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn handle_start_solver(
+    app_handle: tauri::AppHandle,
+    runner: tauri::State<'_, SolverRunner>,
+    solver_manager: tauri::State<'_, SolverManager>,
+) -> Result<RunState, String> {
+    println!("called handle_start_solver");
+    if solver_manager.solver.lock().unwrap().is_none() {
+        return Err("No solver".to_string());
+    }
+    let mut worker = runner.worker.lock().unwrap();
+    {
+        let mut state = runner.state.lock().unwrap();
+        if *state != RunState::Idle {
+            return Err(format!("solver is {:?}", *state));
+        }
+        *state = RunState::Running;
+    }
+    //前回のスレッドは終了済みなので回収だけする
+    if let Some(handle) = worker.take() {
+        let _ = handle.join();
+    }
+    let handle = app_handle.clone();
+    *worker = Some(std::thread::spawn(move || run_worker(handle)));
+    Ok(RunState::Running)
+}
+
+#[tauri::command]
+pub fn handle_pause_solver(runner: tauri::State<'_, SolverRunner>) -> Result<RunState, String> {
+    println!("called handle_pause_solver");
+    if runner.get_state() != RunState::Running {
+        return Err("solver is not running".to_string());
+    }
+    runner.set_state(RunState::Paused);
+    Ok(RunState::Paused)
+}
+
+#[tauri::command]
+pub fn handle_resume_solver(runner: tauri::State<'_, SolverRunner>) -> Result<RunState, String> {
+    println!("called handle_resume_solver");
+    if runner.get_state() != RunState::Paused {
+        return Err("solver is not paused".to_string());
+    }
+    runner.set_state(RunState::Running);
+    Ok(RunState::Running)
+}
+
+#[tauri::command]
+pub fn handle_stop_solver(runner: tauri::State<'_, SolverRunner>) -> Result<RunState, String> {
+    println!("called handle_stop_solver");
+    match runner.get_state() {
+        RunState::Running | RunState::Paused => {
+            runner.set_state(RunState::Stopping);
+            Ok(RunState::Stopping)
+        }
+        state => Ok(state),
+    }
+}
+
+#[tauri::command]
+pub fn handle_get_solver_state(runner: tauri::State<'_, SolverRunner>) -> Result<RunState, String> {
+    Ok(runner.get_state())
+}
+
+#[tauri::command]
+pub fn handle_get_last_result(
+    runner: tauri::State<'_, SolverRunner>,
+) -> Result<Option<RunResult>, String> {
+    Ok(runner.last_result.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub fn handle_get_stop_criteria(
+    runner: tauri::State<'_, SolverRunner>,
+) -> Result<StopCriteria, String> {
+    Ok(runner.criteria.lock().unwrap().clone())
+}
+
+//実行中の変更は次の実行から使う
+#[tauri::command]
+pub fn handle_set_stop_criteria(
+    runner: tauri::State<'_, SolverRunner>,
+    criteria: StopCriteria,
+) -> Result<StopCriteria, String> {
+    println!("called handle_set_stop_criteria {:?}", criteria);
+    criteria.validate()?;
+    *runner.criteria.lock().unwrap() = criteria.clone();
+    Ok(criteria)
+}
@@ -0,0 +1,217 @@
+use super::aco::aco_parameters::AcoParameters;
+use super::aco::aco_solver::{ACOSolver, SolverRng};
+use super::aco::stagnation::Restart;
+use super::aco::violations::Violations;
+use super::annealing::AnnealingSolver;
+use super::constraint::{
+    self, ConstraintContext, ConstraintKind, Placements, Score, ScoreBreakdown,
+};
+use super::genetic::GeneticSolver;
+use super::time_table::cell::ActiveCell;
+use crate::input::Input;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SolverKind {
+    Aco,
+    SimulatedAnnealing,
+    Genetic,
+}
+
+impl Default for SolverKind {
+    fn default() -> Self {
+        SolverKind::Aco
+    }
+}
+
+//時間割を探す手法に共通する操作。実行スレッドと時間割の変換はこれだけを使う
+pub trait Solver: Send {
+    fn kind(&self) -> SolverKind;
+
+    //探索の状態と乱数をシードから作り直す。固定はそのまま
+    //実行スレッドはStartのたびに最初に呼ぶので、前の実行の続きにはならない
+    fn initialize(&mut self);
+
+    //1世代(1反復)進める
+    fn step(&mut self);
+
+    //この実行でこれまでに見つかった最も良い解。どの手法でも時間割と違反の表示にはこれを使う
+    fn best_solution(&self) -> Option<&Placements>;
+
+    //実行の終わりに呼ぶ
+    fn finish_run(&mut self) {}
+
+    //locks[class_index] = Some((room, period))
+    fn get_locks(&self) -> &Vec<Option<(usize, usize)>>;
+
+    fn set_locks(&mut self, locks: &Vec<Option<(usize, usize)>>);
+
+    fn get_parameters(&self) -> &AcoParameters;
+
+    fn get_input(&self) -> &Input;
+
+    fn get_seed(&self) -> u64;
+
+    fn constraint_context(&self) -> ConstraintContext<'_> {
+        ConstraintContext::new(self.get_input(), self.get_parameters())
+    }
+
+    //時間割の色に使う、その場所の選ばれやすさ(0から1)。持たない手法はNone
+    fn slot_preference(&self, _class_index: usize, _room: usize, _period: usize) -> Option<f64> {
+        None
+    }
+
+    fn as_aco_mut(&mut self) -> Option<&mut ACOSolver> {
+        None
+    }
+
+    //探索の多様さ(0から1)。ACOではフェロモンのエントロピー。測らない手法はNone
+    fn diversity(&self) -> Option<f64> {
+        None
+    }
+
+    //停滞して再出発した記録
+    fn restarts(&self) -> &[Restart] {
+        &[]
+    }
+
+    //best_solutionの規則ごとのコスト。毎世代呼ぶので、数え直さずに覚えている値を返す
+    fn breakdown(&self) -> ScoreBreakdown;
+
+    fn score(&self) -> Score {
+        self.breakdown().score()
+    }
+
+    //今の世代の最良解(焼きなましでは今の解)の評価値。探索の進み具合の表示に使う
+    fn current_score(&self) -> Score;
+
+    fn violations(&self, kind: ConstraintKind) -> Vec<Violations> {
+        match self.best_solution() {
+            Some(placements) => {
+                constraint::violations_of_kind(&self.constraint_context(), placements, kind)
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+pub fn new_solver(parameters: AcoParameters, input: Input) -> Box<dyn Solver> {
+    match parameters.solver {
+        SolverKind::Aco => Box::new(ACOSolver::new(parameters, input)),
+        SolverKind::SimulatedAnnealing => Box::new(AnnealingSolver::new(parameters, input)),
+        SolverKind::Genetic => Box::new(GeneticSolver::new(parameters, input)),
+    }
+}
+
+pub fn locked_classes(locks: &Vec<Option<(usize, usize)>>) -> Vec<bool> {
+    locks.iter().map(|x| x.is_some()).collect()
+}
+
+//空いている候補の場所から無作為に選ぶ。固定された授業は先に置く
+pub fn random_placements(
+    ctx: &ConstraintContext,
+    locks: &Vec<Option<(usize, usize)>>,
+    rng: &mut SolverRng,
+) -> Placements {
+    let mut placements = ctx.new_placements();
+    for (class_index, lock) in locks.iter().enumerate() {
+        if let Some((room, period)) = lock {
+            placements.place(&ctx.classes[class_index], *room, *period);
+        }
+    }
+    let mut order = (0..ctx.classes.len()).collect::<Vec<usize>>();
+    order.shuffle(rng);
+    for class_index in order {
+        if !placements.is_placed(class_index) {
+            place_randomly(ctx, &mut placements, class_index, rng);
+        }
+    }
+    placements
+}
+
+//固定をplacementsに反映する。固定した場所にいた授業は外して、空いている場所へ置き直す
+pub fn apply_locks(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    locks: &Vec<Option<(usize, usize)>>,
+    rng: &mut SolverRng,
+) {
+    let mut displaced = Vec::new();
+    for (class_index, lock) in locks.iter().enumerate() {
+        let (room, period) = match lock {
+            Some(lock) => *lock,
+            None => continue,
+        };
+        if placements.get(class_index) == Some([room, period]) {
+            continue;
+        }
+        let class = &ctx.classes[class_index];
+        placements.remove(class);
+        for time in placements.span(class, period) {
+            if let Some(other) = placements.occupant(room, time) {
+                placements.remove(&ctx.classes[other]);
+                displaced.push(other);
+            }
+        }
+        placements.place(class, room, period);
+    }
+    for class_index in displaced {
+        if !placements.is_placed(class_index) {
+            place_randomly(ctx, placements, class_index, rng);
+        }
+    }
+}
+
+//置ける場所がなければ置かない
+pub fn place_randomly(
+    ctx: &ConstraintContext,
+    placements: &mut Placements,
+    class_index: usize,
+    rng: &mut SolverRng,
+) -> bool {
+    let class = &ctx.classes[class_index];
+    let mut slots = Vec::new();
+    for &room in class.room_candidates_indexes.iter() {
+        for period in 0..placements.num_of_periods() {
+            if placements.is_free(room, period, class.serial_size) {
+                slots.push([room, period]);
+            }
+        }
+    }
+    match slots.choose(rng) {
+        Some(&[room, period]) => {
+            placements.place(class, room, period);
+            true
+        }
+        None => false,
+    }
+}
+
+pub struct SolverManager {
+    pub solver: Mutex<Option<Box<dyn Solver>>>,
+}
+
+//画面上の固定状態をソルバーに反映する
+#[tauri::command]
+pub fn handle_read_cells(
+    solver_manager: tauri::State<'_, SolverManager>,
+    cells: Vec<Option<ActiveCell>>,
+) -> Result<(), String> {
+    let mut managed_solver = solver_manager.solver.lock().unwrap();
+    if let Some(solver) = managed_solver.as_mut() {
+        let mut locks = solver.get_locks().clone();
+        for active_cell in cells.iter().flatten() {
+            if active_cell.is_locked.unwrap_or(false) {
+                locks[active_cell.class_index] = Some((active_cell.room, active_cell.period));
+            } else {
+                locks[active_cell.class_index] = None;
+            }
+        }
+        solver.set_locks(&locks);
+        return Ok(());
+    }
+    return Err("solver is not initialized".to_string());
+}
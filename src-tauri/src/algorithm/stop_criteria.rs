@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum StopReason {
+    TimeLimit,
+    IterationLimit,
+    TargetScore,
+    NoHardViolations,
+    NoImprovement,
+    //UIから止められた
+    Stopped,
+    NoSolver,
+}
+
+//どれか1つでも満たしたら止める。Noneの条件は使わない
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StopCriteria {
+    pub time_limit_secs: Option<f64>,
+    //Noneならパラメータのmax_iterationsを使う
+    pub max_iterations: Option<usize>,
+    pub target_score: Option<f64>,
+    pub stop_on_no_hard_violations: bool,
+    pub max_iterations_without_improvement: Option<usize>,
+}
+
+impl Default for StopCriteria {
+    fn default() -> Self {
+        StopCriteria {
+            time_limit_secs: None,
+            max_iterations: None,
+            target_score: None,
+            stop_on_no_hard_violations: false,
+            max_iterations_without_improvement: None,
+        }
+    }
+}
+
+//判定に使う実行中の状態
+pub struct RunStatus {
+    pub iteration: usize,
+    //送信や保存の時間も含めた実時間。一時停止していた時間は含まない
+    pub elapsed: Duration,
+    pub best_score: f64,
+    //hardのコストが0ならtrue
+    pub is_feasible: bool,
+    pub iterations_without_improvement: usize,
+}
+
+impl StopCriteria {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(time_limit_secs) = self.time_limit_secs {
+            if !time_limit_secs.is_finite() || time_limit_secs <= 0.0 {
+                return Err("timeLimitSecs must be positive".to_string());
+            }
+        }
+        if self.max_iterations == Some(0) {
+            return Err("maxIterations must be at least 1".to_string());
+        }
+        if let Some(target_score) = self.target_score {
+            if !target_score.is_finite() {
+                return Err("targetScore must be a number".to_string());
+            }
+        }
+        if self.max_iterations_without_improvement == Some(0) {
+            return Err("maxIterationsWithoutImprovement must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn check(&self, status: &RunStatus, default_max_iterations: usize) -> Option<StopReason> {
+        if let Some(target_score) = self.target_score {
+            if status.best_score <= target_score {
+                return Some(StopReason::TargetScore);
+            }
+        }
+        if self.stop_on_no_hard_violations && status.is_feasible {
+            return Some(StopReason::NoHardViolations);
+        }
+        if status.iteration >= self.max_iterations.unwrap_or(default_max_iterations) {
+            return Some(StopReason::IterationLimit);
+        }
+        if let Some(time_limit_secs) = self.time_limit_secs {
+            if status.elapsed.as_secs_f64() >= time_limit_secs {
+                return Some(StopReason::TimeLimit);
+            }
+        }
+        if let Some(max_iterations_without_improvement) = self.max_iterations_without_improvement {
+            if status.iterations_without_improvement >= max_iterations_without_improvement {
+                return Some(StopReason::NoImprovement);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status() -> RunStatus {
+        RunStatus {
+            iteration: 10,
+            elapsed: Duration::from_secs(5),
+            best_score: 100.0,
+            is_feasible: false,
+            iterations_without_improvement: 3,
+        }
+    }
+
+    #[test]
+    fn default_stops_only_at_max_iterations() {
+        let criteria = StopCriteria::default();
+        assert_eq!(criteria.check(&status(), 11), None);
+        assert_eq!(
+            criteria.check(&status(), 10),
+            Some(StopReason::IterationLimit)
+        );
+    }
+
+    #[test]
+    fn each_criterion_stops_the_run() {
+        let cases = [
+            (
+                StopCriteria {
+                    time_limit_secs: Some(5.0),
+                    ..StopCriteria::default()
+                },
+                StopReason::TimeLimit,
+            ),
+            (
+                StopCriteria {
+                    max_iterations: Some(10),
+                    ..StopCriteria::default()
+                },
+                StopReason::IterationLimit,
+            ),
+            (
+                StopCriteria {
+                    target_score: Some(100.0),
+                    ..StopCriteria::default()
+                },
+                StopReason::TargetScore,
+            ),
+            (
+                StopCriteria {
+                    max_iterations_without_improvement: Some(3),
+                    ..StopCriteria::default()
+                },
+                StopReason::NoImprovement,
+            ),
+        ];
+        for (criteria, reason) in cases {
+            assert_eq!(criteria.check(&status(), usize::MAX), Some(reason));
+        }
+    }
+
+    #[test]
+    fn unmet_criteria_keep_running() {
+        let criteria = StopCriteria {
+            time_limit_secs: Some(6.0),
+            max_iterations: Some(11),
+            target_score: Some(99.0),
+            stop_on_no_hard_violations: true,
+            max_iterations_without_improvement: Some(4),
+        };
+        assert_eq!(criteria.check(&status(), 1), None);
+        let feasible = RunStatus {
+            is_feasible: true,
+            ..status()
+        };
+        assert_eq!(
+            criteria.check(&feasible, 1),
+            Some(StopReason::NoHardViolations)
+        );
+    }
+}
@@ -4,18 +4,18 @@ pub mod cell;
 use crate::input::class::Class;
 use cell::ActiveCell;
 use cell::BlankCell;
+use cell::UnscheduledCell;
 use core::num;
 use core::str;
 use std::collections::BTreeSet;
 use std::error::Error;
 use std::sync::Mutex;
 
-use super::aco::aco_solver::ACOSolver;
-use super::aco::aco_solver::ACOSolverManager;
 use super::aco::violations;
 use super::aco::violations::CellsViolation;
-use super::aco::violations::Violations;
-use crate::input::room::Room;
+use super::constraint::{self, ConstraintContext, Placements};
+use super::runner::SolverRunner;
+use super::solver::{Solver, SolverManager};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -26,6 +26,9 @@ pub struct TimeTable {
     pub process_table: Vec<Vec<Option<Class>>>,
     pub room_size: usize,
     pub period_size: usize,
+    //置かれていない授業。idは2 * room_size * period_size + class_index
+    #[serde(default)]
+    pub unscheduled: Vec<UnscheduledCell>,
 }
 //TODO timeTableに関する操作を抽象化して、それぞれの操作を関数で行う。
 //座標とindexを連動させるべきではない
@@ -51,6 +54,7 @@ impl TimeTable {
             process_table,
             room_size,
             period_size,
+            unscheduled: Vec::new(),
         }
     }
 
@@ -64,17 +68,17 @@ impl TimeTable {
         period: usize,
         class: Class,
         color: Option<String>,
-        solver: &ACOSolver,
+        solver: &dyn Solver,
     ) {
         for i in 0..class.serial_size {
             self.process_table[room][period + i] = Some(class.clone());
         }
         let mut is_locked = None;
-        if let Some(_) = solver.colony.get_graph().get_classes_is_locked(class.index) {
+        if let Some(_) = solver.get_locks()[class.index] {
             is_locked = Some(true);
         }
         let id = room * self.period_size + period;
-        let tearchers = solver.input.get_teachers();
+        let tearchers = solver.get_input().get_teachers();
         self.class_list[class.index] = Some(ActiveCell {
             id: id + self.period_size * self.room_size,
             period: period,
@@ -97,6 +101,48 @@ impl TimeTable {
         });
     }
 
+    pub fn add_unscheduled(&mut self, class: &Class, solver: &dyn Solver) {
+        let teachers = solver.get_input().get_teachers();
+        self.unscheduled.push(UnscheduledCell {
+            id: self.unscheduled_id(class.index),
+            class_index: class.index,
+            class_name: format!("{},{}", class.index, class.name),
+            teachers: Some(
+                class
+                    .teacher_indexes
+                    .iter()
+                    .map(|&x| teachers[x].name.clone())
+                    .collect(),
+            ),
+            size: Some(class.serial_size),
+        });
+    }
+
+    //空きセルが0..n、授業のセルがn..2n、置かれていない授業が2nから(n = room_size * period_size)
+    fn unscheduled_id(&self, class_index: usize) -> usize {
+        2 * self.room_size * self.period_size + class_index
+    }
+
+    pub fn is_unscheduled_id(&self, id: usize) -> bool {
+        id >= self.unscheduled_id(0)
+    }
+
+    //ドラッグされた授業。時間割のセルとunscheduledの両方から探す
+    pub fn active_class_index(&self, active_id: usize) -> Option<usize> {
+        if self.is_unscheduled_id(active_id) {
+            let class_index = active_id - self.unscheduled_id(0);
+            return self
+                .unscheduled
+                .iter()
+                .find(|x| x.class_index == class_index)
+                .map(|x| x.class_index);
+        }
+        let cell = active_id.checked_sub(self.room_size * self.period_size)?;
+        self.process_table[cell / self.period_size][cell % self.period_size]
+            .as_ref()
+            .map(|x| x.index)
+    }
+
     pub fn debug_class_list(&self) {
         println!("class_list size{}", self.class_list.len());
         for i in self.class_list.iter() {
@@ -121,14 +167,8 @@ impl TimeTable {
         }
     }
 
-    fn update_violations(
-        &mut self,
-        room: usize,
-        period: usize,
-        room_list: &Vec<Room>,
-        one_day_length: usize,
-    ) {
-        let violations = self.get_new_violations(room, period, room_list, one_day_length);
+    fn update_violations(&mut self, room: usize, period: usize, ctx: &ConstraintContext) {
+        let violations = self.get_new_violations(room, period, ctx);
         let class_idx = self.process_table[room][period].as_ref().unwrap().index;
         self.class_list[class_idx].as_mut().unwrap().violations = Some(violations);
     }
@@ -153,7 +193,7 @@ impl TimeTable {
         to_room: usize,
         to_period: usize,
         color: Option<String>,
-        solver: &ACOSolver,
+        solver: &dyn Solver,
     ) {
         let class = self.get_class(from_room, from_period);
         let mut pre_violations: Option<CellsViolation> = None;
@@ -164,210 +204,86 @@ impl TimeTable {
         }
         self.remove_class(from_room, from_period);
         self.add_class(to_room, to_period, class.clone().unwrap(), color, solver);
-        let room_list = solver.input.get_rooms();
-        let violations = self.get_new_violations(
-            to_room,
-            to_period,
-            room_list,
-            solver.parameters.num_of_day_lengths,
-        );
-        let class_idx = self.process_table[to_room][to_period]
-            .as_ref()
-            .unwrap()
-            .index;
-        self.class_list[class_idx].as_mut().unwrap().violations = Some(violations);
-        if let Some(violations) = pre_violations {
-            for violation in violations.same_student_same_time {
-                let period = violation.period;
-                for room in violation.rooms {
-                    self.update_violations(
-                        room,
-                        period,
-                        room_list,
-                        solver.parameters.num_of_day_lengths,
-                    );
-                }
-            }
-        }
+        let ctx = solver.constraint_context();
+        self.update_violations(to_room, to_period, &ctx);
         let mut post_violations: Option<CellsViolation> = None;
         if let Some(input_class) = &class {
             if let Some(cell_input) = &self.class_list[input_class.index] {
                 post_violations = cell_input.violations.clone();
             }
         }
-        if let Some(violations) = post_violations {
-            for violation in violations.same_student_same_time {
-                let period = violation.period;
-                for room in violation.rooms {
-                    self.update_violations(
-                        room,
-                        period,
-                        room_list,
-                        solver.parameters.num_of_day_lengths,
-                    );
-                }
-            }
-        }
+        self.update_neighbor_violations(vec![pre_violations, post_violations], &ctx);
         self.debug_process_table();
     }
 
-    pub fn calc_same_student_same_time(&self, room_id: usize, period_id: usize) -> Vec<Violations> {
-        let mut violations = Vec::<Violations>::new();
-        let serial_size = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .serial_size;
-        let students = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .students_group_indexes
-            .clone();
-        let class_idx = self.process_table[room_id][period_id]
+    //unscheduledの授業を(room, period)に置く
+    pub fn schedule_class(
+        &mut self,
+        class_index: usize,
+        room: usize,
+        period: usize,
+        color: Option<String>,
+        solver: &dyn Solver,
+    ) {
+        self.unscheduled.retain(|x| x.class_index != class_index);
+        let class = solver.get_input().get_classes()[class_index].clone();
+        self.add_class(room, period, class, color, solver);
+        let ctx = solver.constraint_context();
+        self.update_violations(room, period, &ctx);
+        let violations = self.class_list[class_index]
             .as_ref()
-            .unwrap()
-            .index;
-        for time in period_id..(period_id + serial_size) {
-            for room in 0..self.room_size {
-                if time == period_id && room == room_id {
-                    continue;
-                }
-                if let Some(class) = self.process_table[room][time].as_ref() {
-                    if class.index == class_idx {
-                        continue;
-                    }
-                    let mut common_students = class.students_group_indexes.clone();
-                    common_students.retain(|&x| students.contains(&x));
-                    if common_students.len() > 0 {
-                        violations.push(Violations {
-                            period: time,
-                            rooms: vec![room],
-                        });
-                    }
-                }
-            }
-        }
-        violations
-    }
-
-    pub fn common_ids(ids1: &Vec<usize>, ids2: &Vec<usize>) -> Vec<usize> {
-        let mut common_ids = Vec::<usize>::new();
-        for id in ids1 {
-            if ids2.contains(id) {
-                common_ids.push(*id);
-            }
-        }
-        common_ids
+            .and_then(|x| x.violations.clone());
+        self.update_neighbor_violations(vec![violations], &ctx);
+        self.debug_process_table();
     }
 
-    pub fn calc_same_teacher_same_time(&self, room_id: usize, period_id: usize) -> Vec<Violations> {
-        let mut violations = Vec::<Violations>::new();
-        let serial_size = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .serial_size;
-        let teachers = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .teacher_indexes
-            .clone();
-        let class_idx = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .index;
-        for time in period_id..(period_id + serial_size) {
-            for room in 0..self.room_size {
-                if time == period_id && room == room_id {
-                    continue;
-                }
-                if let Some(class) = self.process_table[room][time].as_ref() {
-                    if class.index == class_idx {
-                        continue;
-                    }
-                    let mut common_teachers = class.teacher_indexes.clone();
-                    common_teachers.retain(|&x| teachers.contains(&x));
-                    if common_teachers.len() > 0 {
-                        violations.push(Violations {
-                            period: time,
-                            rooms: vec![room],
-                        });
+    //動かした授業と衝突していた相手の違反も更新する
+    fn update_neighbor_violations(
+        &mut self,
+        violations_list: Vec<Option<CellsViolation>>,
+        ctx: &ConstraintContext,
+    ) {
+        for violations in violations_list.into_iter().flatten() {
+            for violation in violations
+                .same_student_same_time
+                .into_iter()
+                .chain(violations.same_teacher_same_time.into_iter())
+            {
+                let period = violation.period;
+                for room in violation.rooms {
+                    if let Some(neighbor) = self.process_table[room][period].as_ref() {
+                        let cell = self.class_list[neighbor.index].as_ref().unwrap();
+                        let (room, period) = (cell.room, cell.period);
+                        self.update_violations(room, period, ctx);
                     }
                 }
             }
         }
-        violations
-    }
-
-    pub fn calc_capacity_over(
-        &self,
-        room_id: usize,
-        period_id: usize,
-        room_list: &Vec<Room>,
-    ) -> Vec<Violations> {
-        let mut violations = Vec::<Violations>::new();
-        let num_of_students = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .num_of_students;
-        let capacity = room_list[room_id].capacity;
-        if num_of_students > capacity {
-            violations.push(Violations {
-                period: period_id,
-                rooms: vec![room_id],
-            });
-        }
-        violations
     }
 
-    pub fn calc_strabble_days(
-        &self,
-        room_id: usize,
-        period_id: usize,
-        one_day_length: usize,
-    ) -> Vec<Violations> {
-        let mut violations = Vec::<Violations>::new();
-        let serial_size = self.process_table[room_id][period_id]
-            .as_ref()
-            .unwrap()
-            .serial_size;
-        let start_in_a_day = period_id % one_day_length;
-        if start_in_a_day + serial_size > one_day_length {
-            violations.push(Violations {
-                period: period_id,
-                rooms: vec![room_id],
-            });
+    //class_listから制約の計算に使う配置を作る
+    pub fn get_placements(&self, ctx: &ConstraintContext) -> Placements {
+        let mut placements = ctx.new_placements();
+        for cell in self.class_list.iter().flatten() {
+            placements.place(&ctx.classes[cell.class_index], cell.room, cell.period);
         }
-        violations
+        placements
     }
 
     pub fn get_new_violations(
         &self,
         room_id: usize,
         period_id: usize,
-        room_list: &Vec<Room>,
-        one_day_length: usize,
+        ctx: &ConstraintContext,
     ) -> CellsViolation {
-        let same_student_same_time = self.calc_same_student_same_time(room_id, period_id);
-        let same_teacher_same_time = self.calc_same_teacher_same_time(room_id, period_id);
-        let capacity_over = self.calc_capacity_over(room_id, period_id, room_list);
-        let strabble_days = self.calc_strabble_days(room_id, period_id, one_day_length);
-        let mut is_violated: bool = false;
-        if same_student_same_time.len() > 0
-            || same_teacher_same_time.len() > 0
-            || capacity_over.len() > 0
-            || strabble_days.len() > 0
-        {
-            is_violated = true;
-        }
-        CellsViolation {
-            is_violated,
-            same_student_same_time,
-            same_teacher_same_time,
-            capacity_over,
-            strabble_days,
-        }
+        let class_idx = self.process_table[room_id][period_id]
+            .as_ref()
+            .unwrap()
+            .index;
+        constraint::cell_violations(ctx, &self.get_placements(ctx), class_idx)
     }
 
-    pub fn updated_by_process_table(&mut self, solver: &ACOSolver) {
+    pub fn updated_by_process_table(&mut self, solver: &dyn Solver) {
         let is_locked_list = self
             .class_list
             .iter()
@@ -376,9 +292,9 @@ impl TimeTable {
         self.class_list = Vec::<Option<ActiveCell>>::new();
         self.dragging_cell_data = vec![
             vec![vec![None; self.period_size]; self.room_size];
-            solver.input.get_classes().len()
+            solver.get_input().get_classes().len()
         ];
-        let teachers = solver.input.get_teachers();
+        let teachers = solver.get_input().get_teachers();
         for i in 0..self.room_size {
             for j in 0..self.period_size {
                 if let Some(class) = self.process_table[i][j].as_ref() {
@@ -411,15 +327,22 @@ impl TimeTable {
     }
 }
 
-pub fn convert_solver_to_timetable(solver: &ACOSolver) -> Result<TimeTable, Box<dyn Error>> {
+pub fn convert_solver_to_timetable(solver: &dyn Solver) -> Result<TimeTable, Box<dyn Error>> {
     let mut time_table = TimeTable::new(
-        solver.parameters.num_of_rooms,
-        solver.parameters.num_of_periods,
-        solver.input.get_classes().len(),
+        solver.get_parameters().num_of_rooms,
+        solver.get_parameters().num_of_periods,
+        solver.get_input().get_classes().len(),
     );
-    let best_ant = solver.get_best_ant().ok_or("No best ant found")?;
-    let classes = solver.input.get_classes();
-    for (class_id, &[room_id, period_id]) in best_ant.get_corresponding_crp().iter().enumerate() {
+    let best_solution = solver.best_solution().ok_or("No best solution found")?;
+    let classes = solver.get_input().get_classes();
+    for (class_id, slot) in best_solution.get_slots().iter().enumerate() {
+        let [room_id, period_id] = match slot {
+            Some(slot) => *slot,
+            None => {
+                time_table.add_unscheduled(&classes[class_id], solver);
+                continue;
+            }
+        };
         let class = classes[class_id].clone();
         time_table.add_class(
             room_id,
@@ -429,24 +352,19 @@ pub fn convert_solver_to_timetable(solver: &ACOSolver) -> Result<TimeTable, Box<
             solver,
         );
     }
-    for cell in time_table.clone().class_list {
-        if let Some(cell) = cell {
-            let violations = Some(time_table.get_new_violations(
-                cell.room,
-                cell.period,
-                &solver.input.get_rooms(),
-                solver.parameters.num_of_day_lengths,
-            ));
-            //println!("violations:{:?}",&violations);
-            time_table.class_list[cell.class_index]
-                .as_mut()
-                .unwrap()
-                .violations = violations;
-        }
+    let ctx = solver.constraint_context();
+    let placements = time_table.get_placements(&ctx);
+    for cell in time_table.class_list.iter_mut().flatten() {
+        cell.violations = Some(constraint::cell_violations(
+            &ctx,
+            &placements,
+            cell.class_index,
+        ));
     }
     Ok(time_table)
 }
 
+//ソルバーと両方ロックするときは、必ずソルバー、時間割の順にロックする
 pub struct TimeTableManager {
     pub timetable_manager: Mutex<Option<TimeTable>>,
 }
@@ -457,14 +375,14 @@ pub fn save_timetable(timetable_manager: tauri::State<'_, TimeTableManager>, tim
 }
 
 fn calc_color_init(
-    solver: &ACOSolver,
+    solver: &dyn Solver,
     class_id: usize,
     room_id: usize,
     period_id: usize,
 ) -> String {
     let mut res = get_pheromone_color(solver, class_id, room_id, period_id);
 
-    if let Some(is_lock) = solver.colony.get_graph().get_classes_is_locked(class_id) {
+    if let Some(is_lock) = solver.get_locks()[class_id] {
         if is_lock.0 == room_id as usize && is_lock.1 == period_id as usize {
             res = "#AAAAFF".to_string();
         }
@@ -473,21 +391,13 @@ fn calc_color_init(
 }
 
 fn get_pheromone_color(
-    solver: &ACOSolver,
+    solver: &dyn Solver,
     class_id: usize,
     room_id: usize,
     period_id: usize,
 ) -> String {
     let mut res = String::from("#FFFFFF");
-    if let Some(ant) = solver.get_best_ant() {
-        let (rp_v, prov_v) =
-            ant.calc_prob_from_v_igunore_visited(class_id, solver.colony.get_graph());
-        let mut prov = 0.0;
-        for (i, rp) in rp_v.iter().enumerate() {
-            if rp[0] == room_id && rp[1] == period_id {
-                prov = prov_v[i];
-            }
-        }
+    if let Some(prov) = solver.slot_preference(class_id, room_id, period_id) {
         let color = (255.0 - (prov * 255.0)) as u8;
         let hex = format!("{:02x}", color);
         res = format!("#ff{}{}ff", hex, hex);
@@ -495,7 +405,7 @@ fn get_pheromone_color(
     res
 }
 
-fn calc_color_from_cell(solver: &ACOSolver, active_cell: &ActiveCell) -> String {
+fn calc_color_from_cell(solver: &dyn Solver, active_cell: &ActiveCell) -> String {
     if active_cell.is_locked.unwrap_or(false) {
         return "#AAAAFF".to_string();
     }
@@ -508,23 +418,14 @@ fn calc_color_from_cell(solver: &ACOSolver, active_cell: &ActiveCell) -> String
 #[tauri::command]
 pub fn is_swappable(
     time_table_manager: tauri::State<'_, TimeTableManager>,
-    solver_manager: tauri::State<'_, ACOSolverManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
     over_id: usize,
     active_id: usize,
 ) -> Result<bool, String> {
     println!("called is_swappable,{},{}", over_id, active_id);
     let managed_solver = solver_manager.solver.lock().unwrap();
-    let parameter = managed_solver
-        .as_ref()
-        .ok_or("No solver found")?
-        .parameters
-        .clone();
-
-    let periods_size = parameter.num_of_periods;
-    let rooms_size = parameter.num_of_rooms;
+    let solver = managed_solver.as_deref().ok_or("No solver found")?;
     let mut is_swappable = true;
-    let over_room = over_id / periods_size;
-    let over_period = over_id % periods_size;
     if let Some(time_table) = time_table_manager
         .timetable_manager
         .lock()
@@ -533,65 +434,84 @@ pub fn is_swappable(
     {
         time_table.debug_process_table();
         time_table.debug_class_list();
-        println!(
-            "active_id:{},room:{},period:{}",
-            active_id - periods_size * rooms_size,
-            (active_id - rooms_size * periods_size) / periods_size,
-            active_id % periods_size
-        );
-        let active_size = time_table.process_table
-            [(active_id - rooms_size * periods_size) / periods_size][active_id % periods_size]
-            .as_ref()
-            .unwrap()
-            .serial_size;
-        //no over the day
-        let active_index = time_table.process_table
-            [(active_id - rooms_size * periods_size) / periods_size][active_id % periods_size]
-            .as_ref()
-            .unwrap()
-            .index;
-        for day in over_period..(over_period + active_size) {
-            if day >= periods_size {
-                is_swappable = false;
-                break;
-            }
-            if let Some(class) = time_table.process_table[over_room][day].as_ref() {
-                if class.index != active_index {
-                    is_swappable = false;
-                    break;
-                }
+        //unscheduledの授業は時間割にないので、入力の授業を見る
+        let active_index = time_table
+            .active_class_index(active_id)
+            .ok_or("No active class found")?;
+        println!("active_id:{},class:{}", active_id, active_index);
+        is_swappable = can_place(time_table, solver, active_index, over_id);
+    }
+    return Ok(is_swappable);
+}
+
+//over_idの場所が授業の候補の教室で、続きのコマも含めて他の授業がないか
+fn can_place(
+    time_table: &TimeTable,
+    solver: &dyn Solver,
+    class_index: usize,
+    over_id: usize,
+) -> bool {
+    let periods_size = time_table.period_size;
+    let over_room = over_id / periods_size;
+    let over_period = over_id % periods_size;
+    if over_room >= time_table.room_size {
+        return false;
+    }
+    let active_class = &solver.get_input().get_classes()[class_index];
+    //only candidate rooms
+    if !active_class.room_candidates_indexes.contains(&over_room) {
+        return false;
+    }
+    //no over the day
+    for day in over_period..(over_period + active_class.serial_size) {
+        if day >= periods_size {
+            return false;
+        }
+        if let Some(class) = time_table.process_table[over_room][day].as_ref() {
+            if class.index != class_index {
+                return false;
             }
         }
     }
-    return Ok(is_swappable);
+    true
 }
 
 //Assume all swap destinations are blankcells
+//activeがunscheduledの授業なら時間割に置く
 #[tauri::command]
 pub fn handle_swap_cell(
     timetable_manager: tauri::State<'_, TimeTableManager>,
-    solver_manager: tauri::State<'_, ACOSolverManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
+    runner: tauri::State<'_, SolverRunner>,
     over_id: usize,
     active_id: usize,
 ) -> Result<TimeTable, String> {
     println!("called handle_swap_cell,{},{}", over_id, active_id);
+    runner.check_idle()?;
+    let managed_solver = solver_manager.solver.lock().unwrap();
+    let solver = managed_solver.as_deref().ok_or("No solver found")?;
     let mut managed_timetable = timetable_manager.timetable_manager.lock().unwrap();
-    let solver = solver_manager.solver.lock().unwrap();
     if let Some(time_table) = managed_timetable.as_mut() {
         let room_size = time_table.room_size;
         let period_size = time_table.period_size;
-        let new_id = active_id - period_size * room_size;
-        let active_period = new_id % period_size;
-        let active_room = new_id / period_size;
         let over_period = over_id % period_size;
         let over_room = over_id / period_size;
-        let index = time_table.process_table[active_room][active_period]
-            .as_ref()
-            .unwrap()
-            .index;
+        let index = time_table
+            .active_class_index(active_id)
+            .ok_or("No active class found")?;
+        //画面のis_swappableを通らずに呼ばれても壊れないように、ここでも確かめる
+        if !can_place(time_table, solver, index, over_id) {
+            return Err("class cannot be placed there".to_string());
+        }
         //これだと、一つ前のフェロモンが出てくる
-        let mut color =
-            get_pheromone_color(solver.as_ref().unwrap(), index, over_room, over_period);
+        let mut color = get_pheromone_color(solver, index, over_room, over_period);
+        if time_table.is_unscheduled_id(active_id) {
+            time_table.schedule_class(index, over_room, over_period, Some(color), solver);
+            return Ok(time_table.clone());
+        }
+        let new_id = active_id - period_size * room_size;
+        let active_period = new_id % period_size;
+        let active_room = new_id / period_size;
         let is_locked = time_table.class_list[index]
             .as_ref()
             .unwrap()
@@ -606,7 +526,7 @@ pub fn handle_swap_cell(
             over_room,
             over_period,
             Some(color),
-            solver.as_ref().unwrap(),
+            solver,
         );
         return Ok(time_table.clone());
     }
@@ -616,13 +536,18 @@ pub fn handle_swap_cell(
 #[tauri::command]
 pub fn handle_switch_lock(
     timetable_manager: tauri::State<'_, TimeTableManager>,
-    solver_manager: tauri::State<'_, ACOSolverManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
+    runner: tauri::State<'_, SolverRunner>,
     id: usize,
 ) -> Result<TimeTable, String> {
     println!("called handle_switch_lock,{}", id);
-    let mut managed_timetable = timetable_manager.timetable_manager.lock().unwrap();
+    runner.check_idle()?;
     let solver = solver_manager.solver.lock().unwrap();
+    let mut managed_timetable = timetable_manager.timetable_manager.lock().unwrap();
     if let Some(time_table) = managed_timetable.as_mut() {
+        if time_table.is_unscheduled_id(id) {
+            return Err("unscheduled class cannot be locked".to_string());
+        }
         let room = (id - time_table.room_size * time_table.period_size) / time_table.period_size;
         let period = (id - time_table.room_size * time_table.period_size) % time_table.period_size;
         let class_index = time_table.process_table[room][period]
@@ -641,7 +566,7 @@ pub fn handle_switch_lock(
                 .unwrap_or(false),
         );
         time_table.class_list[class_index].as_mut().unwrap().color = Some(calc_color_from_cell(
-            solver.as_ref().unwrap(),
+            solver.as_deref().unwrap(),
             time_table.class_list[class_index].as_ref().unwrap(),
         ));
         return Ok(time_table.clone());
@@ -48,3 +48,14 @@ pub struct BlankCell {
     pub room: usize,
     pub color: Option<String>,
 }
+
+//置ける場所がなく時間割に載らなかった授業。時間割の外に並べ、ドラッグして置ける
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnscheduledCell {
+    pub id: usize,
+    pub class_index: usize,
+    pub class_name: String,
+    pub teachers: Option<Vec<String>>,
+    pub size: Option<usize>,
+}
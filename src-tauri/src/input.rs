@@ -1,14 +1,26 @@
+use crate::algorithm::aco::aco_parameters::AcoParametersManager;
+use crate::algorithm::aco::parameter_preset::PresetStore;
 use serde::{Deserialize, Serialize};
 
+use self::calendar::Calendar;
+use self::dataset::{DatasetPaths, DatasetSource};
+use self::input_error::{closest_name, InputError, InputErrorKind, RowReader};
 use self::{student_group::StudentGroup, teacher::Teacher};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::{error::Error, vec};
+use std::vec;
+use tauri::Manager;
 
+pub mod calendar;
 pub mod class;
-mod column;
+pub mod column;
+pub mod dataset;
+pub mod input_error;
 pub mod room;
-mod student_group;
+pub mod student_group;
+pub mod table;
 pub mod teacher;
+pub mod validation;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Input {
@@ -16,159 +28,290 @@ pub struct Input {
     rooms: Vec<room::Room>,
     student_groups: Vec<student_group::StudentGroup>,
     teachers: Vec<teacher::Teacher>,
+    calendar: Calendar,
 }
 
-const TEACHERS_CSV_PATH: &str = "./csvdata/themed_research/teachers.csv";
-const STUDENT_GROUPS_CSV_PATH: &str = "./csvdata/themed_research/student_groups.csv";
-const CLASSES_CSV_PATH: &str = "./csvdata/themed_research/classes.csv";
-const ROOMS_CSV_PATH: &str = "./csvdata/themed_research/rooms.csv";
-
 impl Input {
-    pub fn new() -> Input {
-        let teachers = Input::read_teachers_from_csv(&TEACHERS_CSV_PATH.to_string()).unwrap();
-        let rooms = Input::read_rooms_from_csv(&ROOMS_CSV_PATH.to_string()).unwrap();
+    pub fn new(paths: &DatasetPaths) -> Result<Input, Vec<InputError>> {
+        let mut errors = Vec::new();
+        let teachers = Input::read_teachers_from_csv(&paths.teachers, &mut errors);
+        let rooms = Input::read_rooms_from_csv(&paths.rooms, &mut errors);
         let student_groups =
-            Input::read_student_groups_from_csv(&STUDENT_GROUPS_CSV_PATH.to_string()).unwrap();
+            Input::read_student_groups_from_csv(&paths.student_groups, &mut errors);
         let classes = Input::read_classes_from_csv(
-            &CLASSES_CSV_PATH.to_string(),
+            &paths.classes,
             &teachers,
             &rooms,
             &student_groups,
-        )
-        .unwrap();
-        Input {
+            &mut errors,
+        );
+        let calendar = Input::read_calendar_from_json(&paths.calendar, &mut errors);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(Input {
             classes,
             rooms,
             student_groups,
             teachers,
+            calendar,
+        })
+    }
+
+    //calendar.jsonが無いデータセットは従来の週構成で読む
+    fn read_calendar_from_json(file_path: &Path, errors: &mut Vec<InputError>) -> Calendar {
+        if !file_path.is_file() {
+            println!("no calendar at {:?}, use default calendar", file_path);
+            return Calendar::default();
+        }
+        let text = match std::fs::read_to_string(file_path) {
+            Ok(text) => text,
+            Err(e) => {
+                errors.push(InputError::file_error(file_path, e.to_string()));
+                return Calendar::default();
+            }
+        };
+        match serde_json::from_str::<Calendar>(&text) {
+            Ok(calendar) => {
+                if calendar.num_of_periods() == 0 {
+                    errors.push(InputError::file_error(
+                        file_path,
+                        "calendar has no periods".to_string(),
+                    ));
+                }
+                calendar
+            }
+            Err(e) => {
+                let mut error = InputError::file_error(file_path, e.to_string());
+                error.kind = InputErrorKind::MalformedRow;
+                error.line = Some(e.line() as u64);
+                errors.push(error);
+                Calendar::default()
+            }
         }
     }
 
-    fn read_teachers_from_csv(file_path: &String) -> Result<Vec<teacher::Teacher>, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
+    //1行ずつread_rowに渡し、失敗した行はerrorsに積んで読み飛ばす
+    fn read_rows<T, F>(file_path: &Path, errors: &mut Vec<InputError>, mut read_row: F) -> Vec<T>
+    where
+        F: FnMut(&RowReader, usize) -> Result<T, Vec<InputError>>,
+    {
+        let mut res = Vec::new();
+        let mut rdr = match csv::ReaderBuilder::new()
             .has_headers(true)
-            .from_path(file_path)?;
-        let mut teachers = Vec::new();
-        for (index, result) in rdr.records().enumerate() {
-            let record = result?;
-            let id = record[0].parse::<usize>().unwrap();
-            let name = record[1].to_string();
-            println!("{:?}", record[2].to_string());
-            let absent_days = if record[2].is_empty() {
-                vec![]
-            } else {
-                record[2]
-                    .split(",")
-                    .map(|x| x.parse::<usize>().unwrap())
-                    .collect()
+            .from_path(file_path)
+        {
+            Ok(rdr) => rdr,
+            Err(e) => {
+                errors.push(InputError::file_error(file_path, e.to_string()));
+                return res;
+            }
+        };
+        let headers = match rdr.headers() {
+            Ok(headers) => headers.clone(),
+            Err(e) => {
+                errors.push(InputError::from_csv_error(file_path, &e));
+                return res;
+            }
+        };
+        for result in rdr.records() {
+            let record = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    errors.push(InputError::from_csv_error(file_path, &e));
+                    continue;
+                }
             };
-            let index = index as usize;
-            teachers.push(teacher::Teacher {
+            let row = RowReader::new(file_path, &headers, &record);
+            match read_row(&row, res.len()) {
+                Ok(item) => res.push(item),
+                Err(mut row_errors) => errors.append(&mut row_errors),
+            }
+        }
+        res
+    }
+
+    fn read_teachers_from_csv(
+        file_path: &Path,
+        errors: &mut Vec<InputError>,
+    ) -> Vec<teacher::Teacher> {
+        Input::read_rows(file_path, errors, Input::teacher_from_row)
+    }
+
+    pub fn teacher_from_row(
+        row: &RowReader,
+        index: usize,
+    ) -> Result<teacher::Teacher, Vec<InputError>> {
+        let mut row_errors = Vec::new();
+        let id = row.usize_field(0).map_err(|e| row_errors.push(e)).ok();
+        let name = row.field(1).map_err(|e| row_errors.push(e)).ok();
+        let mut absent_days = Vec::new();
+        match row.field(2) {
+            Ok(days) => {
+                for day in days.split(",").filter(|x| !x.is_empty()) {
+                    match row.parse_usize(2, day) {
+                        Ok(day) => absent_days.push(day),
+                        Err(e) => row_errors.push(e),
+                    }
+                }
+            }
+            Err(e) => row_errors.push(e),
+        }
+        match (id, name) {
+            (Some(id), Some(name)) if row_errors.is_empty() => Ok(teacher::Teacher {
                 id,
                 index,
-                name,
+                name: name.to_string(),
                 absent_days,
-            });
+            }),
+            _ => Err(row_errors),
         }
+    }
 
-        Ok(teachers)
+    fn read_rooms_from_csv(file_path: &Path, errors: &mut Vec<InputError>) -> Vec<room::Room> {
+        Input::read_rows(file_path, errors, Input::room_from_row)
     }
 
-    fn read_rooms_from_csv(file_path: &String) -> Result<Vec<room::Room>, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .from_path(file_path)?;
-        let mut rooms = Vec::new();
-        for (index, result) in rdr.records().enumerate() {
-            let record = result?;
-            let index = index as usize;
-            let id = record[0].parse::<usize>().unwrap();
-            let name = record[1].to_string();
-            let capacity = record[2].parse::<usize>().unwrap();
-            rooms.push(room::Room {
+    pub fn room_from_row(row: &RowReader, index: usize) -> Result<room::Room, Vec<InputError>> {
+        let mut row_errors = Vec::new();
+        let id = row.usize_field(0).map_err(|e| row_errors.push(e)).ok();
+        let name = row.field(1).map_err(|e| row_errors.push(e)).ok();
+        let capacity = row.usize_field(2).map_err(|e| row_errors.push(e)).ok();
+        match (id, name, capacity) {
+            (Some(id), Some(name), Some(capacity)) => Ok(room::Room {
                 id,
                 index,
-                name,
+                name: name.to_string(),
                 capacity,
-            });
+            }),
+            _ => Err(row_errors),
         }
-        Ok(rooms)
     }
 
     fn read_student_groups_from_csv(
-        file_path: &String,
-    ) -> Result<Vec<student_group::StudentGroup>, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .from_path(file_path)?;
-        let mut student_groups = Vec::new();
-        for (index, result) in rdr.records().enumerate() {
-            let record = result?;
-            let id = record[0].parse::<usize>().unwrap();
-            let name = record[1].to_string();
-            let index = index as usize;
-            student_groups.push(student_group::StudentGroup { id, index, name });
+        file_path: &Path,
+        errors: &mut Vec<InputError>,
+    ) -> Vec<student_group::StudentGroup> {
+        Input::read_rows(file_path, errors, Input::student_group_from_row)
+    }
+
+    pub fn student_group_from_row(
+        row: &RowReader,
+        index: usize,
+    ) -> Result<student_group::StudentGroup, Vec<InputError>> {
+        let mut row_errors = Vec::new();
+        let id = row.usize_field(0).map_err(|e| row_errors.push(e)).ok();
+        let name = row.field(1).map_err(|e| row_errors.push(e)).ok();
+        match (id, name) {
+            (Some(id), Some(name)) => Ok(student_group::StudentGroup {
+                id,
+                index,
+                name: name.to_string(),
+            }),
+            _ => Err(row_errors),
         }
-        Ok(student_groups)
+    }
+
+    //カンマ区切りの名前をindexに変換する。見つからない名前は近い候補を添えてエラーにする
+    fn find_indexes<'a, I>(
+        row: &RowReader,
+        column: usize,
+        kind: InputErrorKind,
+        names: I,
+        row_errors: &mut Vec<InputError>,
+    ) -> Vec<usize>
+    where
+        I: Iterator<Item = &'a String> + Clone,
+    {
+        let mut res = Vec::new();
+        let field = match row.field(column) {
+            Ok(field) => field,
+            Err(e) => {
+                row_errors.push(e);
+                return res;
+            }
+        };
+        for i in field.split(",") {
+            if i == "" {
+                continue;
+            }
+            if let Some(add) = names.clone().position(|x| x == i) {
+                res.push(add);
+            } else {
+                row_errors.push(row.error(
+                    kind.clone(),
+                    column,
+                    i,
+                    "name not found",
+                    closest_name(i, names.clone()),
+                ));
+            }
+        }
+        res
     }
 
     fn read_classes_from_csv(
-        file_path: &String,
+        file_path: &Path,
         teachers: &Vec<Teacher>,
         rooms: &Vec<room::Room>,
         student_groups: &Vec<StudentGroup>,
-    ) -> Result<Vec<class::Class>, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .from_path(file_path)?;
-        let mut classes = Vec::new();
-
-        for (index, result) in rdr.records().enumerate() {
-            let record = result?;
-            let index = index as usize;
-            let id = record[0].parse::<usize>().unwrap();
-            let name = record[1].to_string();
-            let mut teacher_indexes = Vec::new();
-            for i in record[2].split(",") {
-                if i == "" {
-                    continue;
-                }
-                if let Some(add) = teachers.iter().position(|x| x.name == i) {
-                    teacher_indexes.push(add as usize);
-                } else {
-                    panic!("teacher not found {}", i);
-                }
-            }
-            let mut room_candidates_indexes = Vec::new();
-            for i in record[3].split(",") {
-                if let Some(add) = rooms.iter().position(|x| x.name == i) {
-                    room_candidates_indexes.push(add as usize);
-                } else {
-                    panic!("room not found");
-                }
-            }
-            let mut students_group_indexes = Vec::new();
-            for i in record[4].split(",") {
-                if let Some(add) = student_groups.iter().position(|x| x.name == i) {
-                    students_group_indexes.push(add as usize);
-                } else {
-                    panic!("student_group not found: {}", i);
-                }
+        errors: &mut Vec<InputError>,
+    ) -> Vec<class::Class> {
+        Input::read_rows(file_path, errors, |row, index| {
+            Input::class_from_row(row, index, teachers, rooms, student_groups)
+        })
+    }
+
+    pub fn class_from_row(
+        row: &RowReader,
+        index: usize,
+        teachers: &Vec<Teacher>,
+        rooms: &Vec<room::Room>,
+        student_groups: &Vec<StudentGroup>,
+    ) -> Result<class::Class, Vec<InputError>> {
+        let mut row_errors = Vec::new();
+        let id = row.usize_field(0).map_err(|e| row_errors.push(e)).ok();
+        let name = row.field(1).map_err(|e| row_errors.push(e)).ok();
+        let teacher_indexes = Input::find_indexes(
+            row,
+            2,
+            InputErrorKind::TeacherNotFound,
+            teachers.iter().map(|x| &x.name),
+            &mut row_errors,
+        );
+        let room_candidates_indexes = Input::find_indexes(
+            row,
+            3,
+            InputErrorKind::RoomNotFound,
+            rooms.iter().map(|x| &x.name),
+            &mut row_errors,
+        );
+        let students_group_indexes = Input::find_indexes(
+            row,
+            4,
+            InputErrorKind::StudentGroupNotFound,
+            student_groups.iter().map(|x| &x.name),
+            &mut row_errors,
+        );
+        let num_of_students = row.usize_field(5).map_err(|e| row_errors.push(e)).ok();
+        let serial_size = row.usize_field(6).map_err(|e| row_errors.push(e)).ok();
+        match (id, name, num_of_students, serial_size) {
+            (Some(id), Some(name), Some(num_of_students), Some(serial_size))
+                if row_errors.is_empty() =>
+            {
+                Ok(class::Class {
+                    id,
+                    index,
+                    num_of_students,
+                    name: name.to_string(),
+                    teacher_indexes,
+                    room_candidates_indexes,
+                    students_group_indexes,
+                    serial_size,
+                })
             }
-            let num_of_students = record[5].parse::<usize>().unwrap();
-            let serial_size = record[6].parse::<usize>().unwrap();
-            classes.push(class::Class {
-                id,
-                index,
-                num_of_students,
-                name,
-                teacher_indexes,
-                room_candidates_indexes,
-                students_group_indexes,
-                serial_size,
-            });
+            _ => Err(row_errors),
         }
-        Ok(classes)
     }
 
     pub fn get_classes(&self) -> &Vec<class::Class> {
@@ -183,19 +326,61 @@ impl Input {
     pub fn get_teachers(&self) -> &Vec<teacher::Teacher> {
         &self.teachers
     }
+    pub fn get_calendar(&self) -> &Calendar {
+        &self.calendar
+    }
 }
 
 pub struct InputManager {
     pub input: Mutex<Option<Input>>,
+    pub dataset_paths: Mutex<Option<DatasetPaths>>,
+}
+
+pub fn get_config_dir(app_handle: &tauri::AppHandle) -> PathBuf {
+    app_handle
+        .path_resolver()
+        .app_config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
 }
 
 #[tauri::command]
-pub fn handle_set_input(input_manager: tauri::State<'_, InputManager>) -> Result<(), String> {
+pub fn handle_set_input(
+    app_handle: tauri::AppHandle,
+    input_manager: tauri::State<'_, InputManager>,
+    source: Option<DatasetSource>,
+) -> Result<DatasetPaths, Vec<InputError>> {
     println!("called handle_set_input");
-    let input = Input::new();
+    let mut managed_paths = input_manager.dataset_paths.lock().unwrap();
+    let paths = DatasetPaths::resolve(&source.unwrap_or_default(), managed_paths.as_ref());
+    let input = Input::new(&paths)?;
     let mut managed_input = input_manager.input.lock().unwrap();
     *managed_input = Some(input);
-    Ok(())
+    let config_dir = get_config_dir(&app_handle);
+    if let Err(e) = paths.save_as_last_used(&config_dir) {
+        println!("failed to save last dataset: {}", e);
+    }
+    //このデータセットで選ばれていたプリセットに切り替える
+    match PresetStore::load(&config_dir) {
+        Ok(store) => {
+            if let Some(settings) = store.dataset_settings(&paths) {
+                *app_handle
+                    .state::<AcoParametersManager>()
+                    .settings
+                    .lock()
+                    .unwrap() = settings;
+            }
+        }
+        Err(e) => println!("failed to load presets: {}", e),
+    }
+    *managed_paths = Some(paths.clone());
+    Ok(paths)
+}
+
+#[tauri::command]
+pub fn handle_get_dataset_paths(
+    input_manager: tauri::State<'_, InputManager>,
+) -> Result<Option<DatasetPaths>, String> {
+    Ok(input_manager.dataset_paths.lock().unwrap().clone())
 }
 
 #[tauri::command]
@@ -208,3 +393,78 @@ pub fn handle_get_rooms(
     }
     return Err("no input".to_string());
 }
+
+//テストで使う小さな入力。3日 x 4時限、3教室、教員3人、学生グループ2つ、授業8つ
+#[cfg(test)]
+pub mod sample {
+    use super::calendar::{Calendar, Day, PeriodSlot};
+    use super::class::Class;
+    use super::room::Room;
+    use super::student_group::StudentGroup;
+    use super::teacher::Teacher;
+    use super::Input;
+
+    pub fn input() -> Input {
+        let days = (0..3)
+            .map(|i| Day {
+                name: format!("day{}", i),
+                english_name: String::new(),
+                num_of_periods: 4,
+            })
+            .collect();
+        let periods = (0..4)
+            .map(|i| PeriodSlot {
+                name: format!("{}限", i + 1),
+                english_name: String::new(),
+                start: None,
+                end: None,
+            })
+            .collect();
+        let rooms = [10, 20, 40]
+            .iter()
+            .enumerate()
+            .map(|(index, &capacity)| Room {
+                id: index,
+                index,
+                name: format!("room{}", index),
+                capacity,
+            })
+            .collect();
+        let teachers = [vec![0, 5], vec![], vec![9, 10, 11]]
+            .into_iter()
+            .enumerate()
+            .map(|(index, absent_days)| Teacher {
+                id: index,
+                index,
+                name: format!("teacher{}", index),
+                absent_days,
+            })
+            .collect();
+        let student_groups = (0..2)
+            .map(|index| StudentGroup {
+                id: index,
+                index,
+                name: format!("group{}", index),
+            })
+            .collect();
+        let classes = (0..8)
+            .map(|index| Class {
+                id: index,
+                index,
+                num_of_students: [5, 15, 30][index % 3],
+                name: format!("class{}", index),
+                teacher_indexes: vec![index % 3],
+                room_candidates_indexes: vec![index % 3, 2],
+                students_group_indexes: vec![index % 2],
+                serial_size: [1, 2, 1, 3][index % 4],
+            })
+            .collect();
+        Input {
+            classes,
+            rooms,
+            student_groups,
+            teachers,
+            calendar: Calendar::new(days, periods),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use super::InputManager;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Day {
+    pub name: String,
+    #[serde(default)]
+    pub english_name: String,
+    pub num_of_periods: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodSlot {
+    pub name: String,
+    #[serde(default)]
+    pub english_name: String,
+    //"09:00"のような時刻
+    #[serde(default)]
+    pub start: Option<String>,
+    #[serde(default)]
+    pub end: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CalendarFile {
+    days: Vec<Day>,
+    periods: Vec<PeriodSlot>,
+}
+
+impl From<CalendarFile> for Calendar {
+    fn from(file: CalendarFile) -> Self {
+        Calendar::new(file.days, file.periods)
+    }
+}
+
+//一週間の構成。各曜日はperiodsの先頭からnum_of_periods個の時限を使う
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", from = "CalendarFile")]
+pub struct Calendar {
+    pub days: Vec<Day>,
+    pub periods: Vec<PeriodSlot>,
+    #[serde(skip)]
+    day_index_of_period: Vec<usize>,
+    #[serde(skip)]
+    day_starts: Vec<usize>,
+}
+
+static DAYS_OF_WEEK: [&str; 7] = ["月", "火", "水", "木", "金", "土", "日"];
+static ENGLISH_DAYS_OF_WEEK: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+impl Default for Calendar {
+    fn default() -> Self {
+        let num_of_days = 5 * 6;
+        let day_length = 4;
+        let days = (0..num_of_days)
+            .map(|i| Day {
+                name: format!("{}曜日", DAYS_OF_WEEK[i % DAYS_OF_WEEK.len()]),
+                english_name: ENGLISH_DAYS_OF_WEEK[i % ENGLISH_DAYS_OF_WEEK.len()].to_string(),
+                num_of_periods: day_length,
+            })
+            .collect();
+        let periods = (0..day_length)
+            .map(|i| PeriodSlot {
+                name: format!("{}限", i + 1),
+                english_name: format!("Period {}", i + 1),
+                start: None,
+                end: None,
+            })
+            .collect();
+        Calendar::new(days, periods)
+    }
+}
+
+impl Calendar {
+    //時限から曜日を引く表もここで作る
+    pub fn new(days: Vec<Day>, periods: Vec<PeriodSlot>) -> Calendar {
+        let mut day_index_of_period = Vec::new();
+        let mut day_starts = Vec::new();
+        for (day_index, day) in days.iter().enumerate() {
+            day_starts.push(day_index_of_period.len());
+            for _ in 0..day.num_of_periods {
+                day_index_of_period.push(day_index);
+            }
+        }
+        Calendar {
+            days,
+            periods,
+            day_index_of_period,
+            day_starts,
+        }
+    }
+
+    pub fn num_of_periods(&self) -> usize {
+        self.day_index_of_period.len()
+    }
+
+    pub fn max_day_length(&self) -> usize {
+        self.days
+            .iter()
+            .map(|x| x.num_of_periods)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn day_of(&self, period: usize) -> usize {
+        self.day_index_of_period[period]
+    }
+
+    pub fn period_in_day(&self, period: usize) -> usize {
+        period - self.day_starts[self.day_of(period)]
+    }
+
+    //periodから始まるserial_size時限の授業が同じ日に収まるか
+    pub fn fits_in_day(&self, period: usize, serial_size: usize) -> bool {
+        if period >= self.num_of_periods() {
+            return false;
+        }
+        let day = &self.days[self.day_of(period)];
+        self.period_in_day(period) + serial_size <= day.num_of_periods
+    }
+
+    pub fn label(&self, period: usize, language: &str) -> String {
+        let day = &self.days[self.day_of(period)];
+        let index_in_day = self.period_in_day(period);
+        let slot = self.periods.get(index_in_day);
+        let (day_name, period_name) = if language == "en" {
+            let day_name = if day.english_name.is_empty() {
+                day.name.clone()
+            } else {
+                day.english_name.clone()
+            };
+            let period_name = match slot {
+                Some(slot) if !slot.english_name.is_empty() => slot.english_name.clone(),
+                _ => format!("Period {}", index_in_day + 1),
+            };
+            (day_name, period_name)
+        } else {
+            let period_name = match slot {
+                Some(slot) => slot.name.clone(),
+                None => format!("{}限", index_in_day + 1),
+            };
+            (day.name.clone(), period_name)
+        };
+        let mut res = format!("{} {}", day_name, period_name);
+        if let Some(PeriodSlot {
+            start: Some(start),
+            end: Some(end),
+            ..
+        }) = slot
+        {
+            res += &format!(" ({}-{})", start, end);
+        }
+        res
+    }
+}
+
+#[tauri::command]
+pub fn handle_get_periods(
+    input_manager: tauri::State<'_, InputManager>,
+    language: Option<String>,
+) -> Result<Vec<String>, String> {
+    let input = input_manager.input.lock().unwrap();
+    if let Some(input) = input.as_ref() {
+        let calendar = input.get_calendar();
+        let language = language.unwrap_or("ja".to_string());
+        return Ok((0..calendar.num_of_periods())
+            .map(|i| calendar.label(i, &language))
+            .collect());
+    }
+    Err("no input".to_string())
+}
@@ -1,3 +1,5 @@
+use super::column::Column;
+use super::Input;
 use serde::{Deserialize, Serialize};
 use std::{error::Error, io, process};
 
@@ -14,6 +16,46 @@ pub struct Class {
 }
 
 impl Class {
+    pub fn columns() -> Vec<Column> {
+        vec![
+            Column::new("id", "id"),
+            Column::new("name", "name"),
+            Column::new("teachers", "teachers"),
+            Column::new("candidate_rooms", "candidate_rooms"),
+            Column::new("student_groups", "student_groups"),
+            Column::new("num_of_students", "num_of_students"),
+            Column::new("serial_size", "serial_size"),
+        ]
+    }
+
+    //indexを名前に戻してCSVと同じ並びにする
+    pub fn to_record(&self, input: &Input) -> Vec<String> {
+        let teachers = self
+            .teacher_indexes
+            .iter()
+            .map(|&x| input.get_teachers()[x].name.clone())
+            .collect::<Vec<_>>();
+        let rooms = self
+            .room_candidates_indexes
+            .iter()
+            .map(|&x| input.get_rooms()[x].name.clone())
+            .collect::<Vec<_>>();
+        let student_groups = self
+            .students_group_indexes
+            .iter()
+            .map(|&x| input.get_student_groups()[x].name.clone())
+            .collect::<Vec<_>>();
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            teachers.join(","),
+            rooms.join(","),
+            student_groups.join(","),
+            self.num_of_students.to_string(),
+            self.serial_size.to_string(),
+        ]
+    }
+
     pub fn get_num_of_students(&self) -> usize {
         self.num_of_students
     }
@@ -3,3 +3,12 @@ pub struct Column {
     pub header: String,
     pub accessor: String,
 }
+
+impl Column {
+    pub fn new(header: &str, accessor: &str) -> Column {
+        Column {
+            header: header.to_string(),
+            accessor: accessor.to_string(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DATASET_DIR: &str = "./csvdata/themed_research";
+const TEACHERS_CSV_NAME: &str = "teachers.csv";
+const STUDENT_GROUPS_CSV_NAME: &str = "student_groups.csv";
+const CLASSES_CSV_NAME: &str = "classes.csv";
+const ROOMS_CSV_NAME: &str = "rooms.csv";
+const CALENDAR_JSON_NAME: &str = "calendar.json";
+const LAST_DATASET_FILE_NAME: &str = "last_dataset.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetPaths {
+    pub teachers: PathBuf,
+    pub rooms: PathBuf,
+    pub student_groups: PathBuf,
+    pub classes: PathBuf,
+    #[serde(default)]
+    pub calendar: PathBuf,
+}
+
+//UIから渡される読み込み元。個別のファイル指定はdirより優先する
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetSource {
+    pub dir: Option<String>,
+    pub teachers: Option<String>,
+    pub rooms: Option<String>,
+    pub student_groups: Option<String>,
+    pub classes: Option<String>,
+    pub calendar: Option<String>,
+}
+
+impl DatasetPaths {
+    pub fn from_dir(dir: &Path) -> DatasetPaths {
+        DatasetPaths {
+            teachers: dir.join(TEACHERS_CSV_NAME),
+            rooms: dir.join(ROOMS_CSV_NAME),
+            student_groups: dir.join(STUDENT_GROUPS_CSV_NAME),
+            classes: dir.join(CLASSES_CSV_NAME),
+            calendar: dir.join(CALENDAR_JSON_NAME),
+        }
+    }
+
+    pub fn resolve(source: &DatasetSource, last_used: Option<&DatasetPaths>) -> DatasetPaths {
+        let mut res = if let Some(dir) = &source.dir {
+            DatasetPaths::from_dir(Path::new(dir))
+        } else if let Some(last_used) = last_used {
+            last_used.clone()
+        } else {
+            DatasetPaths::from_dir(Path::new(DEFAULT_DATASET_DIR))
+        };
+        if let Some(teachers) = &source.teachers {
+            res.teachers = PathBuf::from(teachers);
+        }
+        if let Some(rooms) = &source.rooms {
+            res.rooms = PathBuf::from(rooms);
+        }
+        if let Some(student_groups) = &source.student_groups {
+            res.student_groups = PathBuf::from(student_groups);
+        }
+        if let Some(classes) = &source.classes {
+            res.classes = PathBuf::from(classes);
+        }
+        if let Some(calendar) = &source.calendar {
+            res.calendar = PathBuf::from(calendar);
+        }
+        res
+    }
+
+    //データセットごとの設定を引くためのキー
+    pub fn key(&self) -> String {
+        match self.classes.parent() {
+            Some(dir) => dir.display().to_string(),
+            None => self.classes.display().to_string(),
+        }
+    }
+
+    pub fn load_last_used(config_dir: &Path) -> Option<DatasetPaths> {
+        let text = std::fs::read_to_string(config_dir.join(LAST_DATASET_FILE_NAME)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn save_as_last_used(&self, config_dir: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::create_dir_all(config_dir)?;
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(config_dir.join(LAST_DATASET_FILE_NAME), text)?;
+        Ok(())
+    }
+}
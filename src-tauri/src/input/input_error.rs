@@ -0,0 +1,269 @@
+use csv::StringRecord;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum InputErrorKind {
+    FileNotReadable,
+    MalformedRow,
+    MissingColumn,
+    InvalidNumber,
+    TeacherNotFound,
+    RoomNotFound,
+    StudentGroupNotFound,
+    //授業から参照されている行は消せない
+    StillReferenced,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InputError {
+    pub kind: InputErrorKind,
+    pub file: String,
+    //ヘッダー行を1行目とした行番号
+    pub line: Option<u64>,
+    pub column: Option<String>,
+    pub value: Option<String>,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl InputError {
+    pub fn file_error(file: &Path, message: String) -> InputError {
+        InputError {
+            kind: InputErrorKind::FileNotReadable,
+            file: file.display().to_string(),
+            line: None,
+            column: None,
+            value: None,
+            message,
+            suggestion: None,
+        }
+    }
+
+    pub fn from_csv_error(file: &Path, error: &csv::Error) -> InputError {
+        let line = error.position().map(|x| x.line());
+        let kind = if line.is_some() {
+            InputErrorKind::MalformedRow
+        } else {
+            InputErrorKind::FileNotReadable
+        };
+        InputError {
+            kind,
+            file: file.display().to_string(),
+            line,
+            column: None,
+            value: None,
+            message: error.to_string(),
+            suggestion: None,
+        }
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(column) = &self.column {
+            write!(f, " [{}]", column)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(value) = &self.value {
+            write!(f, " ({:?})", value)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " did you mean {:?}?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InputError {}
+
+//CSVの1行分を読み取りながら、エラーに行と列の情報を付ける
+pub struct RowReader<'a> {
+    file: &'a Path,
+    headers: &'a StringRecord,
+    record: &'a StringRecord,
+}
+
+impl<'a> RowReader<'a> {
+    pub fn new(
+        file: &'a Path,
+        headers: &'a StringRecord,
+        record: &'a StringRecord,
+    ) -> RowReader<'a> {
+        RowReader {
+            file,
+            headers,
+            record,
+        }
+    }
+
+    fn column_name(&self, column: usize) -> String {
+        match self.headers.get(column) {
+            Some(header) if !header.is_empty() => header.to_string(),
+            _ => format!("column {}", column + 1),
+        }
+    }
+
+    pub fn error(
+        &self,
+        kind: InputErrorKind,
+        column: usize,
+        value: &str,
+        message: &str,
+        suggestion: Option<String>,
+    ) -> InputError {
+        InputError {
+            kind,
+            file: self.file.display().to_string(),
+            line: self.record.position().map(|x| x.line()),
+            column: Some(self.column_name(column)),
+            value: Some(value.to_string()),
+            message: message.to_string(),
+            suggestion,
+        }
+    }
+
+    pub fn field(&self, column: usize) -> Result<&'a str, InputError> {
+        self.record.get(column).ok_or_else(|| {
+            self.error(
+                InputErrorKind::MissingColumn,
+                column,
+                "",
+                "column is missing",
+                None,
+            )
+        })
+    }
+
+    pub fn parse_usize(&self, column: usize, value: &str) -> Result<usize, InputError> {
+        value.trim().parse::<usize>().map_err(|_| {
+            self.error(
+                InputErrorKind::InvalidNumber,
+                column,
+                value,
+                "not a non-negative integer",
+                None,
+            )
+        })
+    }
+
+    pub fn usize_field(&self, column: usize) -> Result<usize, InputError> {
+        let value = self.field(column)?;
+        self.parse_usize(column, value)
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut dp: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut prev = dp[0];
+        dp[0] = i;
+        for j in 1..=b.len() {
+            let tmp = dp[j];
+            dp[j] = if a[i - 1] == b[j - 1] {
+                prev
+            } else {
+                1 + prev.min(dp[j]).min(dp[j - 1])
+            };
+            prev = tmp;
+        }
+    }
+    dp[b.len()]
+}
+
+//名前の打ち間違いに対して一番近い候補を返す
+pub fn closest_name<'a, I>(target: &str, candidates: I) -> Option<String>
+where
+    I: Iterator<Item = &'a String>,
+{
+    let max_distance = (target.chars().count() / 2).max(1);
+    candidates
+        .map(|x| (edit_distance(target, x), x))
+        .filter(|(d, _)| *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, x)| x.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn closest_name_suggests_typo() {
+        let candidates = names(&["Tanaka", "Suzuki", "Sato"]);
+        assert_eq!(
+            closest_name("Tanaca", candidates.iter()),
+            Some("Tanaka".to_string())
+        );
+        assert_eq!(
+            closest_name("Suzki", candidates.iter()),
+            Some("Suzuki".to_string())
+        );
+    }
+
+    #[test]
+    fn closest_name_ignores_far_names() {
+        let candidates = names(&["Tanaka", "Suzuki"]);
+        assert_eq!(closest_name("Yamamoto", candidates.iter()), None);
+        assert_eq!(closest_name("x", Vec::<String>::new().iter()), None);
+    }
+
+    #[test]
+    fn closest_name_handles_multibyte_names() {
+        let candidates = names(&["講義室A", "講義室B", "実験室"]);
+        assert_eq!(
+            closest_name("講議室A", candidates.iter()),
+            Some("講義室A".to_string())
+        );
+    }
+
+    //ヘッダーを1行目として、n番目のレコードを読む
+    fn read_record(text: &str, n: usize) -> (StringRecord, StringRecord) {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .flexible(true)
+            .from_reader(text.as_bytes());
+        let headers = rdr.headers().unwrap().clone();
+        let record = rdr.records().nth(n).unwrap().unwrap();
+        (headers, record)
+    }
+
+    #[test]
+    fn row_reader_reports_line_and_column() {
+        let (headers, record) = read_record("id,name,capacity\n1,a,10\n2,b,ten\n", 1);
+        let row = RowReader::new(Path::new("rooms.csv"), &headers, &record);
+        assert_eq!(row.usize_field(0).unwrap(), 2);
+        let error = row.usize_field(2).unwrap_err();
+        assert_eq!(error.kind, InputErrorKind::InvalidNumber);
+        assert_eq!(error.file, "rooms.csv");
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.column, Some("capacity".to_string()));
+        assert_eq!(error.value, Some("ten".to_string()));
+    }
+
+    #[test]
+    fn row_reader_reports_missing_column() {
+        let (headers, record) = read_record("id,name\n1,a\n2\n", 1);
+        let row = RowReader::new(Path::new("teachers.csv"), &headers, &record);
+        let error = row.field(1).unwrap_err();
+        assert_eq!(error.kind, InputErrorKind::MissingColumn);
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.column, Some("name".to_string()));
+        //ヘッダーにもない列は番号で示す
+        let error = row.field(4).unwrap_err();
+        assert_eq!(error.column, Some("column 5".to_string()));
+    }
+}
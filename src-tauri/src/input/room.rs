@@ -1,3 +1,4 @@
+use super::column::Column;
 use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Room {
@@ -8,6 +9,22 @@ pub struct Room {
 }
 
 impl Room {
+    pub fn columns() -> Vec<Column> {
+        vec![
+            Column::new("id", "id"),
+            Column::new("name", "name"),
+            Column::new("capacity", "capacity"),
+        ]
+    }
+
+    pub fn to_record(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.capacity.to_string(),
+        ]
+    }
+
     pub fn get_capacity(&self) -> usize {
         self.capacity
     }
@@ -1,3 +1,4 @@
+use super::column::Column;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -6,3 +7,13 @@ pub struct StudentGroup {
     pub name: String,
     pub index: usize,
 }
+
+impl StudentGroup {
+    pub fn columns() -> Vec<Column> {
+        vec![Column::new("id", "id"), Column::new("name", "name")]
+    }
+
+    pub fn to_record(&self) -> Vec<String> {
+        vec![self.id.to_string(), self.name.clone()]
+    }
+}
@@ -0,0 +1,248 @@
+use super::calendar::Calendar;
+use super::column::Column;
+use super::input_error::{InputError, RowReader};
+use super::{class, room, student_group, teacher, Input};
+use csv::{Position, StringRecord};
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableKind {
+    Teachers,
+    Rooms,
+    StudentGroups,
+    Classes,
+}
+
+impl TableKind {
+    pub fn from_name(name: &str) -> Option<TableKind> {
+        match name {
+            "teachers" => Some(TableKind::Teachers),
+            "rooms" => Some(TableKind::Rooms),
+            "studentGroups" => Some(TableKind::StudentGroups),
+            "classes" => Some(TableKind::Classes),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TableKind::Teachers => "teachers",
+            TableKind::Rooms => "rooms",
+            TableKind::StudentGroups => "studentGroups",
+            TableKind::Classes => "classes",
+        }
+    }
+
+    //授業の表でこの表の名前を並べている列。授業の表自体はNone
+    pub fn class_column(&self) -> Option<usize> {
+        match self {
+            TableKind::Teachers => Some(2),
+            TableKind::Rooms => Some(3),
+            TableKind::StudentGroups => Some(4),
+            TableKind::Classes => None,
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Column> {
+        match self {
+            TableKind::Teachers => teacher::Teacher::columns(),
+            TableKind::Rooms => room::Room::columns(),
+            TableKind::StudentGroups => student_group::StudentGroup::columns(),
+            TableKind::Classes => class::Class::columns(),
+        }
+    }
+}
+
+//CSVと同じ並びの文字列の行。表の編集はこの形で行い、Input::from_recordsで型に戻す
+#[derive(Clone, Debug, Default)]
+pub struct InputRecords {
+    pub teachers: Vec<Vec<String>>,
+    pub rooms: Vec<Vec<String>>,
+    pub student_groups: Vec<Vec<String>>,
+    pub classes: Vec<Vec<String>>,
+}
+
+impl InputRecords {
+    pub fn get(&self, kind: TableKind) -> &Vec<Vec<String>> {
+        match kind {
+            TableKind::Teachers => &self.teachers,
+            TableKind::Rooms => &self.rooms,
+            TableKind::StudentGroups => &self.student_groups,
+            TableKind::Classes => &self.classes,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: TableKind) -> &mut Vec<Vec<String>> {
+        match kind {
+            TableKind::Teachers => &mut self.teachers,
+            TableKind::Rooms => &mut self.rooms,
+            TableKind::StudentGroups => &mut self.student_groups,
+            TableKind::Classes => &mut self.classes,
+        }
+    }
+
+    //kindの行の名前を変えたとき、授業の表の参照も書き換える
+    pub fn rename_reference(&mut self, kind: TableKind, old: &str, new: &str) {
+        let column = match kind.class_column() {
+            Some(column) => column,
+            None => return,
+        };
+        for record in self.classes.iter_mut() {
+            if let Some(field) = record.get_mut(column) {
+                *field = field
+                    .split(",")
+                    .map(|x| if x == old { new } else { x })
+                    .collect::<Vec<_>>()
+                    .join(",");
+            }
+        }
+    }
+
+    //kindの行の名前を参照している授業の名前
+    pub fn referring_classes(&self, kind: TableKind, name: &str) -> Vec<String> {
+        let column = match kind.class_column() {
+            Some(column) => column,
+            None => return Vec::new(),
+        };
+        self.classes
+            .iter()
+            .filter(|record| {
+                record
+                    .get(column)
+                    .is_some_and(|x| x.split(",").any(|x| x == name))
+            })
+            .map(|record| record.get(1).cloned().unwrap_or_default())
+            .collect()
+    }
+}
+
+//元のファイルのヘッダー行。読めなければ列名を使う
+fn original_headers(kind: TableKind, file_path: &Path) -> Vec<String> {
+    let columns = kind
+        .columns()
+        .into_iter()
+        .map(|x| x.header)
+        .collect::<Vec<_>>();
+    let headers = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(file_path)
+        .and_then(|mut rdr| rdr.headers().cloned());
+    match headers {
+        Ok(headers) if headers.len() >= columns.len() => headers
+            .iter()
+            .take(columns.len())
+            .map(|x| x.to_string())
+            .collect(),
+        _ => columns,
+    }
+}
+
+fn parse_records<T, F>(
+    kind: TableKind,
+    records: &Vec<Vec<String>>,
+    errors: &mut Vec<InputError>,
+    mut from_row: F,
+) -> Vec<T>
+where
+    F: FnMut(&RowReader, usize) -> Result<T, Vec<InputError>>,
+{
+    let file = Path::new(kind.name());
+    let headers = StringRecord::from(
+        kind.columns()
+            .into_iter()
+            .map(|x| x.header)
+            .collect::<Vec<_>>(),
+    );
+    let mut res = Vec::new();
+    for (i, record) in records.iter().enumerate() {
+        let mut record = StringRecord::from(record.clone());
+        //ヘッダーを1行目として、CSVに書き出したときの行番号を付ける
+        let mut position = Position::new();
+        position.set_line(i as u64 + 2);
+        record.set_position(Some(position));
+        let row = RowReader::new(file, &headers, &record);
+        match from_row(&row, res.len()) {
+            Ok(item) => res.push(item),
+            Err(mut row_errors) => errors.append(&mut row_errors),
+        }
+    }
+    res
+}
+
+impl Input {
+    pub fn to_records(&self) -> InputRecords {
+        InputRecords {
+            teachers: self.get_teachers().iter().map(|x| x.to_record()).collect(),
+            rooms: self.get_rooms().iter().map(|x| x.to_record()).collect(),
+            student_groups: self
+                .get_student_groups()
+                .iter()
+                .map(|x| x.to_record())
+                .collect(),
+            classes: self
+                .get_classes()
+                .iter()
+                .map(|x| x.to_record(self))
+                .collect(),
+        }
+    }
+
+    pub fn from_records(
+        records: &InputRecords,
+        calendar: Calendar,
+    ) -> Result<Input, Vec<InputError>> {
+        let mut errors = Vec::new();
+        let teachers = parse_records(
+            TableKind::Teachers,
+            &records.teachers,
+            &mut errors,
+            Input::teacher_from_row,
+        );
+        let rooms = parse_records(
+            TableKind::Rooms,
+            &records.rooms,
+            &mut errors,
+            Input::room_from_row,
+        );
+        let student_groups = parse_records(
+            TableKind::StudentGroups,
+            &records.student_groups,
+            &mut errors,
+            Input::student_group_from_row,
+        );
+        let classes = parse_records(
+            TableKind::Classes,
+            &records.classes,
+            &mut errors,
+            |row, index| Input::class_from_row(row, index, &teachers, &rooms, &student_groups),
+        );
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(Input {
+            classes,
+            rooms,
+            student_groups,
+            teachers,
+            calendar,
+        })
+    }
+
+    //ヘッダー行は元のファイル(file_path)のものを残して、out_pathに書く
+    pub fn write_table_csv(
+        &self,
+        kind: TableKind,
+        file_path: &Path,
+        out_path: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        let headers = original_headers(kind, file_path);
+        let mut wtr = csv::Writer::from_path(out_path)?;
+        wtr.write_record(headers)?;
+        for record in self.to_records().get(kind) {
+            wtr.write_record(record)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
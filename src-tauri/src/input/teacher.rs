@@ -1,3 +1,4 @@
+use super::column::Column;
 use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Teacher {
@@ -6,3 +7,26 @@ pub struct Teacher {
     pub name: String,
     pub absent_days: Vec<usize>,
 }
+
+impl Teacher {
+    pub fn columns() -> Vec<Column> {
+        vec![
+            Column::new("id", "id"),
+            Column::new("name", "name"),
+            Column::new("absent_days", "absent_days"),
+        ]
+    }
+
+    pub fn to_record(&self) -> Vec<String> {
+        let absent_days = self
+            .absent_days
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            absent_days.join(","),
+        ]
+    }
+}
@@ -0,0 +1,354 @@
+use super::Input;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationIssueKind {
+    SerialSizeExceedsDay,
+    NoRoomCandidates,
+    NoRoomLargeEnough,
+    TeacherOverloaded,
+    StudentGroupOverloaded,
+    DuplicateId,
+    DuplicateName,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub kind: ValidationIssueKind,
+    //"classes", "teachers", "rooms", "studentGroups"
+    pub table: String,
+    pub indexes: Vec<usize>,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(kind: ValidationIssueKind, table: &str, indexes: Vec<usize>, message: String) -> Self {
+        ValidationIssue {
+            kind,
+            table: table.to_string(),
+            indexes,
+            message,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+fn find_duplicates<K, I>(kind: ValidationIssueKind, table: &str, keys: I) -> Vec<ValidationIssue>
+where
+    K: Ord + std::fmt::Display,
+    I: Iterator<Item = K>,
+{
+    let mut indexes_each_key: BTreeMap<K, Vec<usize>> = BTreeMap::new();
+    for (index, key) in keys.enumerate() {
+        indexes_each_key.entry(key).or_default().push(index);
+    }
+    let label = if kind == ValidationIssueKind::DuplicateId {
+        "id"
+    } else {
+        "name"
+    };
+    indexes_each_key
+        .into_iter()
+        .filter(|(_, indexes)| indexes.len() > 1)
+        .map(|(key, indexes)| {
+            ValidationIssue::new(
+                kind.clone(),
+                table,
+                indexes.clone(),
+                format!("{} {} is used by {} rows", label, key, indexes.len()),
+            )
+        })
+        .collect()
+}
+
+impl Input {
+    //ソルバーに渡す前に、制約を満たしようがない入力を検出する
+    pub fn validate(&self) -> ValidationReport {
+        let mut issues = Vec::new();
+        let num_of_periods = self.get_calendar().num_of_periods();
+        let num_of_day_lengths = self.get_calendar().max_day_length();
+        let classes = self.get_classes();
+        let rooms = self.get_rooms();
+
+        for class in classes.iter() {
+            if class.serial_size > num_of_day_lengths {
+                issues.push(ValidationIssue::new(
+                    ValidationIssueKind::SerialSizeExceedsDay,
+                    "classes",
+                    vec![class.index],
+                    format!(
+                        "{} needs {} serial periods but a day has {}",
+                        class.name, class.serial_size, num_of_day_lengths
+                    ),
+                ));
+            }
+            if class.room_candidates_indexes.is_empty() {
+                issues.push(ValidationIssue::new(
+                    ValidationIssueKind::NoRoomCandidates,
+                    "classes",
+                    vec![class.index],
+                    format!("{} has no candidate rooms", class.name),
+                ));
+            } else if class
+                .room_candidates_indexes
+                .iter()
+                .all(|&x| rooms[x].capacity < class.num_of_students)
+            {
+                issues.push(ValidationIssue::new(
+                    ValidationIssueKind::NoRoomLargeEnough,
+                    "classes",
+                    vec![class.index],
+                    format!(
+                        "{} has {} students but every candidate room is smaller",
+                        class.name, class.num_of_students
+                    ),
+                ));
+            }
+        }
+
+        let mut teacher_loads = vec![0; self.get_teachers().len()];
+        let mut student_group_loads = vec![0; self.get_student_groups().len()];
+        for class in classes.iter() {
+            for &teacher_index in class.teacher_indexes.iter() {
+                teacher_loads[teacher_index] += class.serial_size;
+            }
+            for &student_group_index in class.students_group_indexes.iter() {
+                student_group_loads[student_group_index] += class.serial_size;
+            }
+        }
+        for teacher in self.get_teachers().iter() {
+            let absent_periods = teacher
+                .absent_days
+                .iter()
+                .filter(|&&x| x < num_of_periods)
+                .collect::<BTreeSet<_>>()
+                .len();
+            let available = num_of_periods - absent_periods;
+            if teacher_loads[teacher.index] > available {
+                issues.push(ValidationIssue::new(
+                    ValidationIssueKind::TeacherOverloaded,
+                    "teachers",
+                    vec![teacher.index],
+                    format!(
+                        "{} teaches {} periods but is available for {}",
+                        teacher.name, teacher_loads[teacher.index], available
+                    ),
+                ));
+            }
+        }
+        for student_group in self.get_student_groups().iter() {
+            if student_group_loads[student_group.index] > num_of_periods {
+                issues.push(ValidationIssue::new(
+                    ValidationIssueKind::StudentGroupOverloaded,
+                    "studentGroups",
+                    vec![student_group.index],
+                    format!(
+                        "{} attends {} periods but only {} exist",
+                        student_group.name,
+                        student_group_loads[student_group.index],
+                        num_of_periods
+                    ),
+                ));
+            }
+        }
+
+        let teachers = self.get_teachers();
+        let student_groups = self.get_student_groups();
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateId,
+            "classes",
+            classes.iter().map(|x| x.id),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateName,
+            "classes",
+            classes.iter().map(|x| &x.name),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateId,
+            "rooms",
+            rooms.iter().map(|x| x.id),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateName,
+            "rooms",
+            rooms.iter().map(|x| &x.name),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateId,
+            "teachers",
+            teachers.iter().map(|x| x.id),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateName,
+            "teachers",
+            teachers.iter().map(|x| &x.name),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateId,
+            "studentGroups",
+            student_groups.iter().map(|x| x.id),
+        ));
+        issues.extend(find_duplicates(
+            ValidationIssueKind::DuplicateName,
+            "studentGroups",
+            student_groups.iter().map(|x| &x.name),
+        ));
+        ValidationReport { issues }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::calendar::{Calendar, Day, PeriodSlot};
+    use crate::input::class::Class;
+    use crate::input::room::Room;
+    use crate::input::student_group::StudentGroup;
+    use crate::input::teacher::Teacher;
+
+    //2日 x 3時限
+    fn calendar() -> Calendar {
+        let days = (0..2)
+            .map(|i| Day {
+                name: format!("day{}", i),
+                english_name: String::new(),
+                num_of_periods: 3,
+            })
+            .collect();
+        let periods = (0..3)
+            .map(|i| PeriodSlot {
+                name: format!("{}限", i + 1),
+                english_name: String::new(),
+                start: None,
+                end: None,
+            })
+            .collect();
+        Calendar::new(days, periods)
+    }
+
+    fn class(index: usize, rooms: Vec<usize>, num_of_students: usize, serial_size: usize) -> Class {
+        Class {
+            id: index,
+            index,
+            num_of_students,
+            name: format!("class{}", index),
+            teacher_indexes: vec![0],
+            room_candidates_indexes: rooms,
+            students_group_indexes: vec![0],
+            serial_size,
+        }
+    }
+
+    fn input(classes: Vec<Class>) -> Input {
+        Input {
+            classes,
+            rooms: vec![
+                Room {
+                    id: 0,
+                    index: 0,
+                    name: "small".to_string(),
+                    capacity: 10,
+                },
+                Room {
+                    id: 1,
+                    index: 1,
+                    name: "large".to_string(),
+                    capacity: 50,
+                },
+            ],
+            student_groups: vec![StudentGroup {
+                id: 0,
+                index: 0,
+                name: "group".to_string(),
+            }],
+            teachers: vec![Teacher {
+                id: 0,
+                index: 0,
+                name: "teacher".to_string(),
+                absent_days: Vec::new(),
+            }],
+            calendar: calendar(),
+        }
+    }
+
+    fn kinds(report: &ValidationReport) -> Vec<ValidationIssueKind> {
+        report.issues.iter().map(|x| x.kind.clone()).collect()
+    }
+
+    #[test]
+    fn feasible_input_has_no_issues() {
+        let report = input(vec![class(0, vec![0, 1], 10, 2), class(1, vec![1], 30, 1)]).validate();
+        assert!(report.is_ok(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn detects_class_problems() {
+        let report = input(vec![
+            class(0, vec![0], 5, 4),
+            class(1, Vec::new(), 5, 1),
+            class(2, vec![0], 20, 1),
+        ])
+        .validate();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                ValidationIssueKind::SerialSizeExceedsDay,
+                ValidationIssueKind::NoRoomCandidates,
+                ValidationIssueKind::NoRoomLargeEnough,
+            ]
+        );
+        assert_eq!(report.issues[0].indexes, vec![0]);
+        assert_eq!(report.issues[2].indexes, vec![2]);
+    }
+
+    #[test]
+    fn detects_overloaded_teacher_and_group() {
+        //6時限のうち4時限を使う。教員は3時限休むので足りない
+        let mut input = input(vec![class(0, vec![1], 5, 2), class(1, vec![1], 5, 2)]);
+        input.teachers[0].absent_days = vec![0, 1, 1, 2, 100];
+        assert_eq!(
+            kinds(&input.validate()),
+            vec![ValidationIssueKind::TeacherOverloaded]
+        );
+        input.classes.push(class(2, vec![1], 5, 3));
+        assert_eq!(
+            kinds(&input.validate()),
+            vec![
+                ValidationIssueKind::TeacherOverloaded,
+                ValidationIssueKind::StudentGroupOverloaded,
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_duplicate_ids_and_names() {
+        let mut classes = vec![class(0, vec![1], 5, 1), class(1, vec![1], 5, 1)];
+        classes[1].id = 0;
+        classes[1].name = classes[0].name.clone();
+        let report = input(classes).validate();
+        assert_eq!(
+            kinds(&report),
+            vec![
+                ValidationIssueKind::DuplicateId,
+                ValidationIssueKind::DuplicateName,
+            ]
+        );
+        assert!(report.issues.iter().all(|x| x.indexes == vec![0, 1]));
+        assert!(report.issues.iter().all(|x| x.table == "classes"));
+    }
+}
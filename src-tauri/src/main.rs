@@ -5,60 +5,40 @@ use std::sync::Mutex;
 use tauri::Manager;
 mod algorithm;
 mod input;
+mod project;
 use std::error::Error;
 mod table_editor;
-use algorithm::aco::aco_parameters::AcoParametersManager;
-use algorithm::aco::aco_solver::ACOSolverManager;
+use algorithm::aco::aco_parameters::{AcoParameters, AcoParametersManager, AcoSettings};
+use algorithm::runner::SolverRunner;
+use algorithm::solver::SolverManager;
 use algorithm::time_table;
+use input::dataset::DatasetPaths;
+use input::validation::ValidationReport;
 use input::InputManager;
-use std::time::Instant;
+use project::ProjectManager;
 
 #[tauri::command]
 fn handle_adapt_input(
     input_manager: tauri::State<'_, InputManager>,
-    solver_manager: tauri::State<'_, ACOSolverManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
     aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
-) -> Result<(), String> {
+) -> Result<(), ValidationReport> {
     let input = input_manager.input.lock().unwrap();
     if let Some(input) = input.clone() {
         println!("adapt input to solver.");
-        let parameters = algorithm::aco::aco_parameters::AcoParameters {
-            num_of_ants: 3,
-            num_of_classes: input.get_classes().len(),
-            num_of_rooms: input.get_rooms().len(),
-            num_of_periods: 5 * 6 * 4,
-            num_of_day_lengths: 4,
-            num_of_teachers: input.get_teachers().len(),
-            num_of_students: input.get_student_groups().len(),
-            size_of_frame: 4,
-            q: 10.0,
-            alpha: 1.0,
-            beta: 1.0,
-            rou: 0.5,
-            max_iterations: 100,
-            tau_min: 0.001,
-            tau_max: 100000.0,
-            ant_prob_random: 0.0,
-            super_not_change: 10000,
-        };
-        let solver = Some(algorithm::aco::aco_solver::ACOSolver {
-            parameters: parameters.clone(),
-            colony: algorithm::aco::colony::Colony::new(
-                algorithm::aco::graph::Graph::new(
-                    parameters.clone(),
-                    input.get_classes().clone(),
-                    input.get_rooms().clone(),
-                    input.get_teachers().clone(),
-                ),
-                parameters.clone(),
-            ),
-            best_ant: None,
-            super_ant: None,
-            cnt_super_not_change: 0,
-            input: input,
-        });
+        let report = input.validate();
+        if !report.is_ok() {
+            println!("input has {} issues.", report.issues.len());
+            return Err(report);
+        }
+        let settings = aco_parameters_manager.settings.lock().unwrap().clone();
+        let parameters = AcoParameters::new(&settings, &input);
+        let solver = algorithm::solver::new_solver(parameters, input);
+        println!("solver: {:?}, seed: {}", solver.kind(), solver.get_seed());
+        //シードを含めて実際に使った値を記録する
+        let parameters = solver.get_parameters().clone();
         let mut manarged_solver = solver_manager.solver.lock().unwrap();
-        manarged_solver.replace(solver.unwrap());
+        manarged_solver.replace(solver);
         let mut managed_parameters = aco_parameters_manager.parameters.lock().unwrap();
         managed_parameters.replace(parameters);
     } else {
@@ -68,61 +48,32 @@ fn handle_adapt_input(
 }
 use input::handle_set_input;
 
-#[tauri::command]
-fn handle_aco_run_once(
-    solver_manager: tauri::State<'_, ACOSolverManager>,
-    timetable_manager: tauri::State<'_, time_table::TimeTableManager>,
-) -> Result<time_table::TimeTable, String> {
-    let mut managed_solver = solver_manager.solver.lock().unwrap();
-
-    if let Some(solver) = managed_solver.as_mut() {
-        let mut run_cnt = 0;
-        let start = Instant::now();
-        for _ in 0..10000 {
-            solver.run_aco_times(1);
-            run_cnt += 1;
-            if let Some(best_ant) = &solver.best_ant {
-                println!(
-                    "{:?}",
-                    best_ant.calc_all_path_length(solver.colony.get_graph())
-                );
-                if best_ant.calc_all_path_length(solver.colony.get_graph()) <= 1.5 {
-                    break;
-                }
-            }
-        }
-        let duaration = start.elapsed();
-        println!("times:{:?},{:?}", run_cnt, duaration);
-        let res = time_table::convert_solver_to_timetable(solver).map_err(|e| e.to_string())?;
-        time_table::save_timetable(timetable_manager, res.clone());
-        /*
-        println!(
-            "violations_strict_student{:?}",
-            solver.get_best_ant_same_group_violations_strictly()
-        );
-        println!(
-            "violations_strict_teacher{:?}",
-            solver.get_best_ant_same_teacher_violations_strictly()
-        );
-        println!(
-            "violations_capacity{:?}",
-            solver.get_best_ant_capacity_violations()
-        );
-        println!(
-            "violations_strabble_days{:?}",
-            solver.get_best_ant_strabble_days_violations()
-        );
-        */
-        return Ok(res);
-    }
-    return Err("No ACOSolver".to_string());
-}
-
-use algorithm::aco::aco_parameters::handle_get_periods;
+use algorithm::aco::aco_parameters::handle_get_parameters;
+use algorithm::aco::aco_parameters::handle_set_parameters;
 use algorithm::aco::aco_solver::handle_one_hot_pheromone;
-use algorithm::aco::aco_solver::handle_read_cells;
+use algorithm::aco::parameter_preset::handle_delete_preset;
+use algorithm::aco::parameter_preset::handle_get_presets;
+use algorithm::aco::parameter_preset::handle_save_preset;
+use algorithm::aco::parameter_preset::handle_select_preset;
+use algorithm::runner::handle_get_last_result;
+use algorithm::runner::handle_get_solver_state;
+use algorithm::runner::handle_get_stop_criteria;
+use algorithm::runner::handle_pause_solver;
+use algorithm::runner::handle_resume_solver;
+use algorithm::runner::handle_set_stop_criteria;
+use algorithm::runner::handle_start_solver;
+use algorithm::runner::handle_stop_solver;
+use algorithm::solver::handle_read_cells;
+use input::calendar::handle_get_periods;
+use input::handle_get_dataset_paths;
 use input::handle_get_rooms;
+use project::handle_open_project;
+use project::handle_save_project;
+use project::handle_save_project_as;
+use table_editor::handle_add_row;
+use table_editor::handle_delete_row;
 use table_editor::handle_get_table;
+use table_editor::handle_update_row;
 use time_table::handle_swap_cell;
 use time_table::handle_switch_lock;
 use time_table::is_swappable;
@@ -133,33 +84,62 @@ fn main() -> Result<(), Box<dyn Error>> {
         .invoke_handler(tauri::generate_handler![
             handle_adapt_input,
             handle_set_input,
-            handle_aco_run_once,
+            handle_start_solver,
+            handle_pause_solver,
+            handle_resume_solver,
+            handle_stop_solver,
+            handle_get_solver_state,
+            handle_get_last_result,
+            handle_get_stop_criteria,
+            handle_set_stop_criteria,
             handle_one_hot_pheromone,
             handle_get_table,
+            handle_add_row,
+            handle_update_row,
+            handle_delete_row,
             handle_swap_cell,
             handle_read_cells,
             handle_switch_lock,
             is_swappable,
             handle_get_periods,
-            handle_get_rooms
+            handle_get_rooms,
+            handle_get_dataset_paths,
+            handle_open_project,
+            handle_save_project,
+            handle_save_project_as,
+            handle_get_parameters,
+            handle_set_parameters,
+            handle_get_presets,
+            handle_save_preset,
+            handle_delete_preset,
+            handle_select_preset
         ])
         .setup(|app| {
             let input_manager = InputManager {
                 input: Mutex::new(None),
+                dataset_paths: Mutex::new(DatasetPaths::load_last_used(&input::get_config_dir(
+                    &app.app_handle(),
+                ))),
             };
             app.manage(input_manager);
-            let solver_manager = ACOSolverManager {
+            let solver_manager = SolverManager {
                 solver: Mutex::new(None),
             };
             app.manage(solver_manager);
+            app.manage(SolverRunner::new());
             let timetable_manager = time_table::TimeTableManager {
                 timetable_manager: Mutex::new(None),
             };
             app.manage(timetable_manager);
             let aco_parameters_manager = AcoParametersManager {
                 parameters: Mutex::new(None),
+                settings: Mutex::new(AcoSettings::default()),
             };
             app.manage(aco_parameters_manager);
+            let project_manager = ProjectManager {
+                path: Mutex::new(None),
+            };
+            app.manage(project_manager);
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,216 @@
+use crate::algorithm::aco::aco_parameters::{AcoParameters, AcoParametersManager, ParameterError};
+use crate::algorithm::solver::{self, SolverManager};
+use crate::algorithm::time_table::{TimeTable, TimeTableManager};
+use crate::input::{Input, InputManager};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const PROJECT_FORMAT_VERSION: u32 = 1;
+
+//入力・パラメータ・固定・時間割をまとめて保存する作業ファイル
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub version: u32,
+    pub input: Input,
+    pub parameters: Option<AcoParameters>,
+    //locks[class_index] = Some((room, period))
+    pub locks: Vec<Option<(usize, usize)>>,
+    pub time_table: Option<TimeTable>,
+}
+
+impl Project {
+    pub fn read(path: &Path) -> Result<Project, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)?;
+        let value: serde_json::Value = serde_json::from_str(&text)?;
+        let version = value
+            .get("version")
+            .and_then(|x| x.as_u64())
+            .ok_or("project file has no version")?;
+        if version > PROJECT_FORMAT_VERSION as u64 {
+            return Err(format!(
+                "project file version {} is newer than supported version {}",
+                version, PROJECT_FORMAT_VERSION
+            )
+            .into());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+pub struct ProjectManager {
+    pub path: Mutex<Option<PathBuf>>,
+}
+
+fn collect_project(
+    input_manager: &InputManager,
+    solver_manager: &SolverManager,
+    aco_parameters_manager: &AcoParametersManager,
+    timetable_manager: &TimeTableManager,
+) -> Result<Project, String> {
+    let input = input_manager
+        .input
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("no input")?;
+    let parameters = aco_parameters_manager.parameters.lock().unwrap().clone();
+    let time_table = timetable_manager.timetable_manager.lock().unwrap().clone();
+    let mut locks = vec![None; input.get_classes().len()];
+    if let Some(solver) = solver_manager.solver.lock().unwrap().as_ref() {
+        locks = solver.get_locks().clone();
+    } else if let Some(time_table) = &time_table {
+        for cell in time_table.class_list.iter().flatten() {
+            if cell.is_locked.unwrap_or(false) {
+                locks[cell.class_index] = Some((cell.room, cell.period));
+            }
+        }
+    }
+    Ok(Project {
+        version: PROJECT_FORMAT_VERSION,
+        input,
+        parameters,
+        locks,
+        time_table,
+    })
+}
+
+#[tauri::command]
+pub fn handle_open_project(
+    project_manager: tauri::State<'_, ProjectManager>,
+    input_manager: tauri::State<'_, InputManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
+    aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
+    timetable_manager: tauri::State<'_, TimeTableManager>,
+    path: String,
+) -> Result<Option<TimeTable>, Vec<ParameterError>> {
+    println!("called handle_open_project {}", path);
+    let path = PathBuf::from(path);
+    let project = Project::read(&path).map_err(|e| {
+        vec![ParameterError {
+            field: "project".to_string(),
+            value: path.display().to_string(),
+            message: e.to_string(),
+        }]
+    })?;
+    //手で書き換えたファイルもあるので、handle_set_parametersと同じ検査をしてから使う
+    if let Some(parameters) = &project.parameters {
+        parameters.get_settings().validate()?;
+    }
+    let mut solver = None;
+    if let Some(parameters) = &project.parameters {
+        let mut new_solver = solver::new_solver(parameters.clone(), project.input.clone());
+        new_solver.set_locks(&project.locks);
+        solver = Some(new_solver);
+    }
+    *input_manager.input.lock().unwrap() = Some(project.input);
+    *input_manager.dataset_paths.lock().unwrap() = None;
+    *solver_manager.solver.lock().unwrap() = solver;
+    if let Some(parameters) = &project.parameters {
+        *aco_parameters_manager.settings.lock().unwrap() = parameters.get_settings();
+    }
+    *aco_parameters_manager.parameters.lock().unwrap() = project.parameters;
+    *timetable_manager.timetable_manager.lock().unwrap() = project.time_table.clone();
+    *project_manager.path.lock().unwrap() = Some(path);
+    Ok(project.time_table)
+}
+
+#[tauri::command]
+pub fn handle_save_project(
+    project_manager: tauri::State<'_, ProjectManager>,
+    input_manager: tauri::State<'_, InputManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
+    aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
+    timetable_manager: tauri::State<'_, TimeTableManager>,
+) -> Result<String, String> {
+    let path = project_manager
+        .path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("project has not been saved yet")?;
+    let project = collect_project(
+        &input_manager,
+        &solver_manager,
+        &aco_parameters_manager,
+        &timetable_manager,
+    )?;
+    project.write(&path).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+pub fn handle_save_project_as(
+    project_manager: tauri::State<'_, ProjectManager>,
+    input_manager: tauri::State<'_, InputManager>,
+    solver_manager: tauri::State<'_, SolverManager>,
+    aco_parameters_manager: tauri::State<'_, AcoParametersManager>,
+    timetable_manager: tauri::State<'_, TimeTableManager>,
+    path: String,
+) -> Result<String, String> {
+    println!("called handle_save_project_as {}", path);
+    let path = PathBuf::from(path);
+    let project = collect_project(
+        &input_manager,
+        &solver_manager,
+        &aco_parameters_manager,
+        &timetable_manager,
+    )?;
+    project.write(&path).map_err(|e| e.to_string())?;
+    *project_manager.path.lock().unwrap() = Some(path.clone());
+    Ok(path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::aco::aco_parameters::AcoSettings;
+    use crate::input::sample;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn write_then_read_gives_same_project() {
+        let input = sample::input();
+        let mut locks = vec![None; input.get_classes().len()];
+        locks[0] = Some((0, 1));
+        let project = Project {
+            version: PROJECT_FORMAT_VERSION,
+            parameters: Some(AcoParameters::new(&AcoSettings::default(), &input)),
+            input,
+            locks,
+            time_table: None,
+        };
+        let path = temp_path("project_round_trip");
+        project.write(&path).unwrap();
+        let read = Project::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        let read = read.unwrap();
+        assert_eq!(read.version, PROJECT_FORMAT_VERSION);
+        assert_eq!(read.locks, project.locks);
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&project).unwrap()
+        );
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let path = temp_path("project_newer_version");
+        let text = format!("{{\"version\": {}}}", PROJECT_FORMAT_VERSION + 1);
+        std::fs::write(&path, text).unwrap();
+        let read = Project::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(read.unwrap_err().to_string().contains("newer"));
+    }
+}
@@ -1,234 +1,200 @@
-const TEACHERS_CSV_PATH: &str = "./csvdata/teachers.csv";
-const STUDENT_GROUPS_CSV_PATH: &str = "./csvdata/student_groups.csv";
-const CLASSES_CSV_PATH: &str = "./csvdata/themed_research.csv";
-const ROOMS_CSV_PATH: &str = "./csvdata/rooms.csv";
-use std::error::Error;
-mod class;
-mod column;
-mod room;
-mod student_group;
-mod teacher;
-use class::Class;
-use column::Column;
-use room::Room;
+use crate::input::column::Column;
+use crate::input::input_error::{InputError, InputErrorKind};
+use crate::input::table::TableKind;
+use crate::input::{Input, InputManager};
 use serde::{Deserialize, Serialize};
-use student_group::StudentGroup;
-use teacher::Teacher;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+//エディタに表示する表。列はinputの型から作る
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub enum TableType {
-    Teachers(Teachers),
-    StudentGroups(StudentGroups),
-    Classes(Classes),
-    Rooms(Rooms),
+pub struct Table {
+    pub columns: Vec<Column>,
+    pub data: Vec<BTreeMap<String, String>>,
 }
 
-#[tauri::command]
-pub fn handle_get_table(table_type: String) -> Result<TableType, String> {
-    if table_type == "teachers" {
-        let res = Teachers::new().map_err(|e| e.to_string())?;
-        return Ok(TableType::Teachers(res));
-    } else if table_type == "studentGroups" {
-        let res = StudentGroups::new().map_err(|e| e.to_string())?;
-        return Ok(TableType::StudentGroups(res));
-    } else if table_type == "classes" {
-        let res = Classes::new().map_err(|e| e.to_string())?;
-        return Ok(TableType::Classes(res));
-    } else if table_type == "rooms" {
-        let res = Rooms::new().map_err(|e| e.to_string())?;
-        return Ok(TableType::Rooms(res));
+impl Table {
+    pub fn new(input: &Input, kind: TableKind) -> Table {
+        let columns = kind.columns();
+        let data = input
+            .to_records()
+            .get(kind)
+            .iter()
+            .map(|record| {
+                columns
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(column, value)| (column.accessor.clone(), value.clone()))
+                    .collect()
+            })
+            .collect();
+        Table { columns, data }
     }
-    return Err("Table type not found".to_string());
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Classes {
-    pub columns: Vec<Column>,
-    pub data: Vec<Class>,
+pub enum RowEdit {
+    Add(BTreeMap<String, String>),
+    Update(usize, BTreeMap<String, String>),
+    Delete(usize),
 }
 
-impl Classes {
-    pub fn new() -> Result<Classes, Box<dyn Error>> {
-        Self::read_csv()
-    }
+fn row_to_record(kind: TableKind, row: &BTreeMap<String, String>) -> Vec<String> {
+    kind.columns()
+        .iter()
+        .map(|x| row.get(&x.accessor).cloned().unwrap_or_default())
+        .collect()
+}
 
-    pub fn read_csv() -> Result<Classes, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(CLASSES_CSV_PATH)?;
-        let mut columns = Vec::<Column>::new();
-        let mut classes = Vec::<Class>::new();
-        let first_record = rdr.records().next().unwrap()?;
-        columns.push(Column {
-            header: first_record[0].to_string(),
-            accessor: "id".to_string(),
-        });
-        columns.push(Column {
-            header: first_record[1].to_string(),
-            accessor: "name".to_string(),
-        });
-        columns.push(Column {
-            header: first_record[2].to_string(),
-            accessor: "teachers".to_string(),
-        });
-        columns.push(Column {
-            header: first_record[3].to_string(),
-            accessor: "candidate_rooms".to_string(),
-        });
-        columns.push(Column {
-            header: first_record[4].to_string(),
-            accessor: "student_groups".to_string(),
-        });
-        columns.push(Column {
-            header: first_record[5].to_string(),
-            accessor: "num_of_students".to_string(),
-        });
-        columns.push(Column {
-            header: first_record[6].to_string(),
-            accessor: "serial_size".to_string(),
-        });
-        for result in rdr.records() {
-            let record = result?;
-            let id = record[0].to_string();
-            let name = record[1].to_string();
-            let teachers = record[2].to_string();
-            let candidate_rooms = record[3].to_string();
-            let student_groups = record[4].to_string();
-            let num_of_students = record[5].to_string();
-            let serial_size = record[6].to_string();
-            classes.push(Class {
-                id,
-                name,
-                teachers,
-                candidate_rooms,
-                student_groups,
-                num_of_students,
-                serial_size,
-            });
-        }
-        Ok(Classes {
-            columns: columns,
-            data: classes,
-        })
+fn to_errors(kind: &str, message: &str) -> Vec<InputError> {
+    vec![InputError::file_error(Path::new(kind), message.to_string())]
+}
+
+//授業から参照されている行を消そうとしたときのエラー
+fn referenced_error(kind: TableKind, index: usize, name: &str, classes: &[String]) -> InputError {
+    InputError {
+        kind: InputErrorKind::StillReferenced,
+        file: kind.name().to_string(),
+        line: Some(index as u64 + 2),
+        column: Some("name".to_string()),
+        value: Some(name.to_string()),
+        message: format!("used by classes: {}", classes.join(", ")),
+        suggestion: None,
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Rooms {
-    pub columns: Vec<Column>,
-    pub data: Vec<Room>,
+fn temp_path(path: &Path) -> PathBuf {
+    let mut res = path.as_os_str().to_owned();
+    res.push(".tmp");
+    PathBuf::from(res)
 }
 
-impl Rooms {
-    pub fn new() -> Result<Rooms, Box<dyn Error>> {
-        Self::read_csv()
-    }
-    pub fn read_csv() -> Result<Rooms, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(ROOMS_CSV_PATH)?;
-        let mut columns = Vec::<column::Column>::new();
-        let mut rooms = Vec::new();
-        let first_record = rdr.records().next().unwrap()?;
-        columns.push(column::Column {
-            header: first_record[0].to_string(),
-            accessor: "id".to_string(),
-        });
-        columns.push(column::Column {
-            header: first_record[1].to_string(),
-            accessor: "name".to_string(),
-        });
-        columns.push(column::Column {
-            header: first_record[2].to_string(),
-            accessor: "capacity".to_string(),
-        });
-        for result in rdr.records() {
-            let record = result?;
-            let id = record[0].to_string();
-            let name = record[1].to_string();
-            let capacity = record[2].to_string();
-            rooms.push(Room { id, name, capacity });
+//全ての表を一時ファイルに書き終えてから置き換える。途中で失敗したら元のファイルは変えない
+fn write_tables<'a>(
+    input: &Input,
+    kinds: &[TableKind],
+    file_path: impl Fn(TableKind) -> &'a PathBuf,
+) -> Result<(), String> {
+    let mut written = Vec::new();
+    for &kind in kinds {
+        let path = file_path(kind);
+        let temp = temp_path(path);
+        if let Err(e) = input.write_table_csv(kind, path, &temp) {
+            let _ = std::fs::remove_file(&temp);
+            for (_, temp) in written {
+                let _ = std::fs::remove_file(temp);
+            }
+            return Err(e.to_string());
         }
-        Ok(Rooms {
-            columns: columns,
-            data: rooms,
-        })
+        written.push((path, temp));
     }
+    for (path, temp) in written {
+        std::fs::rename(&temp, path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct StudentGroups {
-    pub columns: Vec<Column>,
-    pub data: Vec<StudentGroup>,
+//編集した行をinputの型に変換し直し、通ったものだけInputManagerとCSVに反映する
+fn edit_table(
+    input_manager: &InputManager,
+    table_type: &str,
+    edit: RowEdit,
+) -> Result<Table, Vec<InputError>> {
+    let kind =
+        TableKind::from_name(table_type).ok_or(to_errors(table_type, "Table type not found"))?;
+    let dataset_paths = input_manager.dataset_paths.lock().unwrap();
+    let mut managed_input = input_manager.input.lock().unwrap();
+    let input = managed_input
+        .as_ref()
+        .ok_or(to_errors(table_type, "no input"))?;
+    let mut records = input.to_records();
+    //授業は名前で参照するので、名前を変えたら授業の表も直し、参照されている行は消さない
+    match edit {
+        RowEdit::Add(row) => records.get_mut(kind).push(row_to_record(kind, &row)),
+        RowEdit::Update(index, row) => {
+            let record = row_to_record(kind, &row);
+            let old = records
+                .get_mut(kind)
+                .get_mut(index)
+                .ok_or(to_errors(table_type, "row index out of range"))?;
+            let (old_name, new_name) = (old[1].clone(), record[1].clone());
+            *old = record;
+            if old_name != new_name {
+                records.rename_reference(kind, &old_name, &new_name);
+            }
+        }
+        RowEdit::Delete(index) => {
+            let name = records
+                .get(kind)
+                .get(index)
+                .ok_or(to_errors(table_type, "row index out of range"))?[1]
+                .clone();
+            let classes = records.referring_classes(kind, &name);
+            if !classes.is_empty() {
+                return Err(vec![referenced_error(kind, index, &name, &classes)]);
+            }
+            records.get_mut(kind).remove(index);
+        }
+    }
+    let new_input = Input::from_records(&records, input.get_calendar().clone())?;
+    if let Some(paths) = dataset_paths.as_ref() {
+        let file_path = |kind: TableKind| match kind {
+            TableKind::Teachers => &paths.teachers,
+            TableKind::Rooms => &paths.rooms,
+            TableKind::StudentGroups => &paths.student_groups,
+            TableKind::Classes => &paths.classes,
+        };
+        let mut kinds = vec![kind];
+        //名前の変更を授業の表にも書き出す
+        if kind.class_column().is_some() && records.classes != input.to_records().classes {
+            kinds.push(TableKind::Classes);
+        }
+        write_tables(&new_input, &kinds, file_path).map_err(|e| to_errors(table_type, &e))?;
+    }
+    let res = Table::new(&new_input, kind);
+    *managed_input = Some(new_input);
+    Ok(res)
 }
 
-impl StudentGroups {
-    pub fn new() -> Result<StudentGroups, Box<dyn Error>> {
-        Self::read_csv()
+#[tauri::command]
+pub fn handle_get_table(
+    input_manager: tauri::State<'_, InputManager>,
+    table_type: String,
+) -> Result<Table, String> {
+    let kind = TableKind::from_name(&table_type).ok_or("Table type not found")?;
+    let input = input_manager.input.lock().unwrap();
+    if let Some(input) = input.as_ref() {
+        return Ok(Table::new(input, kind));
     }
+    return Err("no input".to_string());
+}
 
-    pub fn read_csv() -> Result<StudentGroups, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(STUDENT_GROUPS_CSV_PATH)?;
-        let mut columns = Vec::<column::Column>::new();
-        let mut student_groups = Vec::new();
-        let first_record = rdr.records().next().unwrap()?;
-        columns.push(column::Column {
-            header: first_record[0].to_string(),
-            accessor: "id".to_string(),
-        });
-        columns.push(column::Column {
-            header: first_record[1].to_string(),
-            accessor: "name".to_string(),
-        });
-        for result in rdr.records() {
-            let record = result?;
-            let id = record[0].to_string();
-            let name = record[1].to_string();
-            student_groups.push(StudentGroup { id, name });
-        }
-        Ok(StudentGroups {
-            columns: columns,
-            data: student_groups,
-        })
-    }
+#[tauri::command]
+pub fn handle_add_row(
+    input_manager: tauri::State<'_, InputManager>,
+    table_type: String,
+    row: BTreeMap<String, String>,
+) -> Result<Table, Vec<InputError>> {
+    println!("called handle_add_row {}", table_type);
+    edit_table(&input_manager, &table_type, RowEdit::Add(row))
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Teachers {
-    pub columns: Vec<Column>,
-    pub data: Vec<Teacher>,
+#[tauri::command]
+pub fn handle_update_row(
+    input_manager: tauri::State<'_, InputManager>,
+    table_type: String,
+    index: usize,
+    row: BTreeMap<String, String>,
+) -> Result<Table, Vec<InputError>> {
+    println!("called handle_update_row {} {}", table_type, index);
+    edit_table(&input_manager, &table_type, RowEdit::Update(index, row))
 }
 
-impl Teachers {
-    pub fn new() -> Result<Teachers, Box<dyn Error>> {
-        Self::read_csv()
-    }
-    pub fn read_csv() -> Result<Teachers, Box<dyn Error>> {
-        let mut rdr = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(TEACHERS_CSV_PATH)?;
-        let mut columns = Vec::<column::Column>::new();
-        let mut teachers = Vec::new();
-        let first_record = rdr.records().next().unwrap()?;
-        columns.push(column::Column {
-            header: first_record[0].to_string(),
-            accessor: "id".to_string(),
-        });
-        columns.push(column::Column {
-            header: first_record[1].to_string(),
-            accessor: "name".to_string(),
-        });
-        for result in rdr.records() {
-            let record = result?;
-            let id = record[0].to_string();
-            let name = record[1].to_string();
-            teachers.push(Teacher { id, name });
-        }
-        Ok(Teachers {
-            columns: columns,
-            data: teachers,
-        })
-    }
+#[tauri::command]
+pub fn handle_delete_row(
+    input_manager: tauri::State<'_, InputManager>,
+    table_type: String,
+    index: usize,
+) -> Result<Table, Vec<InputError>> {
+    println!("called handle_delete_row {} {}", table_type, index);
+    edit_table(&input_manager, &table_type, RowEdit::Delete(index))
 }
@@ -1,7 +0,0 @@
-use serde::{Deserialize, Serialize};
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Room {
-    pub id: String,
-    pub name: String,
-    pub capacity: String,
-}
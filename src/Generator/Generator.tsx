@@ -1,9 +1,116 @@
 import Input from "./Input/Input";
 import { invoke } from "@tauri-apps/api/tauri";
+import { listen } from "@tauri-apps/api/event";
 import Grid from "./Grid/Grid";
+import Parameters from "./Parameters/Parameters";
+import StopCriteria from "./StopCriteria/StopCriteria";
 import { useEffect, useState } from "react";
 import { TimeTable } from "./Grid/Grid";
 
+interface DatasetPaths {
+  teachers: string;
+  rooms: string;
+  studentGroups: string;
+  classes: string;
+  calendar: string;
+}
+
+interface InputError {
+  kind: string;
+  file: string;
+  line: number | null;
+  column: string | null;
+  value: string | null;
+  message: string;
+  suggestion: string | null;
+}
+
+const formatInputError = (err: InputError) => {
+  let res = err.file;
+  if (err.line != null) {
+    res += ":" + err.line;
+  }
+  if (err.column != null) {
+    res += " [" + err.column + "]";
+  }
+  res += " " + err.message;
+  if (err.value != null) {
+    res += " (" + err.value + ")";
+  }
+  if (err.suggestion != null) {
+    res += " did you mean " + err.suggestion + "?";
+  }
+  return res;
+};
+
+interface ValidationIssue {
+  kind: string;
+  table: string;
+  indexes: number[];
+  message: string;
+}
+
+interface ValidationReport {
+  issues: ValidationIssue[];
+}
+
+interface SolverProgress {
+  state: string;
+  iteration: number;
+  bestScore: number;
+  superScore: number;
+  hardScore: number;
+  softScore: number;
+  sameGroupViolations: number;
+  sameTeacherViolations: number;
+  capacityViolations: number;
+  strabbleDaysViolations: number;
+  absentDaysViolations: number;
+  unassignedClasses: number;
+  // ACOではフェロモンのエントロピー
+  diversity: number | null;
+  restarts: number;
+}
+
+const formatProgress = (progress: SolverProgress) => {
+  return (
+    progress.state +
+    " iteration:" + progress.iteration +
+    " best:" + progress.bestScore.toFixed(3) +
+    " super:" + progress.superScore.toFixed(3) +
+    " hard:" + progress.hardScore.toFixed(3) +
+    " soft:" + progress.softScore.toFixed(3) +
+    " group:" + progress.sameGroupViolations +
+    " teacher:" + progress.sameTeacherViolations +
+    " capacity:" + progress.capacityViolations +
+    " strabble:" + progress.strabbleDaysViolations +
+    " absent:" + progress.absentDaysViolations +
+    " unassigned:" + progress.unassignedClasses +
+    (progress.diversity != null ? " diversity:" + progress.diversity.toFixed(3) : "") +
+    " restarts:" + progress.restarts
+  );
+};
+
+interface Restart {
+  iteration: number;
+  // "noImprovement" | "lowEntropy"
+  reason: string;
+  mode: string;
+  entropy: number;
+  iterationsWithoutImprovement: number;
+}
+
+interface RunResult {
+  stopReason: string;
+  seed: number | null;
+  // ACOのときだけ入る
+  classOrdering: string | null;
+  elapsedMs: number;
+  progress: SolverProgress | null;
+  restarts: Restart[];
+  timeTable: TimeTable | null;
+}
+
 interface GeneratorProps {
   tableNames: string[];
 }
@@ -12,6 +119,55 @@ const Generator: React.FC<GeneratorProps> = ({ tableNames }) => {
   let [timeTable, setTimeTable] = useState({ classList: [] ,roomSize:0,periodSize:0} as TimeTable);
   let [rooms,SetRooms] = useState([] as string[]);
   let [periods,SetPeriods] = useState([] as string[]);
+  let [datasetDir,SetDatasetDir] = useState("");
+  let [language,SetLanguage] = useState("ja");
+  let [projectPath,SetProjectPath] = useState("");
+  let [loadedFiles,SetLoadedFiles] = useState([] as string[]);
+  let [inputErrors,SetInputErrors] = useState([] as InputError[]);
+  let [validationIssues,SetValidationIssues] = useState([] as ValidationIssue[]);
+  let [progress,SetProgress] = useState(null as SolverProgress | null);
+  let [solverState,SetSolverState] = useState("idle");
+  let [stopReason,SetStopReason] = useState("");
+
+  // ソルバーのスレッドから途中経過と時間割を受け取る
+  useEffect(() => {
+    const unlistenProgress = listen<SolverProgress>("solver-progress", (event) => {
+      SetProgress(event.payload);
+    });
+    const unlistenSnapshot = listen<TimeTable>("solver-snapshot", (event) => {
+      setTimeTable(event.payload);
+    });
+    const unlistenFinished = listen<RunResult>("solver-finished", (event) => {
+      SetProgress(event.payload.progress);
+      SetStopReason(
+        event.payload.stopReason +
+          " seed:" + (event.payload.seed ?? "") +
+          (event.payload.classOrdering != null ? " ordering:" + event.payload.classOrdering : "") +
+          " time:" + event.payload.elapsedMs + "ms" +
+          event.payload.restarts
+            .map((x) => " restart@" + x.iteration + "(" + x.reason + "," + x.mode + ")")
+            .join("")
+      );
+      if (event.payload.timeTable != null) {
+        setTimeTable(event.payload.timeTable);
+      }
+      SetSolverState("idle");
+    });
+    invoke<string>("handle_get_solver_state").then(SetSolverState);
+    return () => {
+      unlistenProgress.then((f) => f());
+      unlistenSnapshot.then((f) => f());
+      unlistenFinished.then((f) => f());
+    };
+  },[]);
+
+  useEffect(() => {
+    invoke<DatasetPaths | null>("handle_get_dataset_paths").then((res) => {
+      if (res != null) {
+        SetLoadedFiles(Object.values(res));
+      }
+    });
+  },[]);
 
   useEffect(() => {
     invoke<string[]>("handle_get_rooms").then((res) => {
@@ -20,37 +176,118 @@ const Generator: React.FC<GeneratorProps> = ({ tableNames }) => {
       SetRooms([err]);
     });
 
-    invoke<string[]>('handle_get_periods').then((res) => {
+    invoke<string[]>('handle_get_periods', { language: language }).then((res) => {
       SetPeriods(res);
     }).catch((err) => {
       SetPeriods([err]);
     });
-  },[timeTable]);
+  },[timeTable, language]);
 
   const sendClassData = () => {
-    invoke("handle_set_input");
+    const source = datasetDir == "" ? null : { dir: datasetDir };
+    invoke<DatasetPaths>("handle_set_input", { source: source })
+      .then((res) => {
+        SetLoadedFiles(Object.values(res));
+        SetInputErrors([]);
+      })
+      .catch((err: InputError[]) => {
+        SetLoadedFiles([]);
+        SetInputErrors(err);
+      });
+  };
+  const openProject = () => {
+    invoke<TimeTable | null>("handle_open_project", { path: projectPath })
+      .then((res) => {
+        if (res != null) {
+          setTimeTable(res);
+        }
+      })
+      .catch((err) => {
+        console.log(err);
+      });
+  };
+  const saveProject = () => {
+    invoke<string>("handle_save_project").catch((err) => {
+      console.log(err);
+    });
+  };
+  const saveProjectAs = () => {
+    invoke<string>("handle_save_project_as", { path: projectPath }).catch((err) => {
+      console.log(err);
+    });
   };
   const generate = () => {
-    invoke("handle_adapt_input");
+    invoke("handle_adapt_input")
+      .then(() => {
+        SetValidationIssues([]);
+      })
+      .catch((err: ValidationReport) => {
+        SetValidationIssues(err.issues);
+      });
   };
-  const run_once = () => {
+  const start = () => {
     if (timeTable.classList.length != 0){
       invoke("handle_read_cells",{cells:timeTable.classList});
     }
-    invoke<TimeTable>("handle_aco_run_once")
+    invoke<string>("handle_start_solver")
       .then((res) => {
-        setTimeTable(res);
+        SetSolverState(res);
+        SetStopReason("");
       })
       .catch((err) => {
         console.log(err);
       });
   };
+  const pause = () => {
+    invoke<string>("handle_pause_solver").then(SetSolverState).catch(console.log);
+  };
+  const resume = () => {
+    invoke<string>("handle_resume_solver").then(SetSolverState).catch(console.log);
+  };
+  const stop = () => {
+    invoke<string>("handle_stop_solver").then(SetSolverState).catch(console.log);
+  };
 
   return (
     <div>
+      <input
+        type="text"
+        placeholder="dataset directory"
+        value={datasetDir}
+        onChange={(e) => SetDatasetDir(e.target.value)}
+      />
       <button onClick={sendClassData}>convert input</button>
+      <input
+        type="text"
+        placeholder="project file"
+        value={projectPath}
+        onChange={(e) => SetProjectPath(e.target.value)}
+      />
+      <button onClick={openProject}>open</button>
+      <button onClick={saveProject}>save</button>
+      <button onClick={saveProjectAs}>save as</button>
+      <select value={language} onChange={(e) => SetLanguage(e.target.value)}>
+        <option value="ja">日本語</option>
+        <option value="en">English</option>
+      </select>
       <button onClick={generate}>set input</button>
-      <button onClick={run_once}>next generation</button>
+      <button onClick={start} disabled={solverState != "idle"}>start</button>
+      <button onClick={pause} disabled={solverState != "running"}>pause</button>
+      <button onClick={resume} disabled={solverState != "paused"}>resume</button>
+      <button onClick={stop} disabled={solverState == "idle"}>stop</button>
+      <div>{progress != null ? formatProgress(progress) : ""}</div>
+      <div>{stopReason != "" ? "stopped: " + stopReason : ""}</div>
+      <StopCriteria />
+      <div>{loadedFiles.join(", ")}</div>
+      <Parameters datasetKey={loadedFiles.join(",")} />
+      <ul>
+        {inputErrors.map((err, index) => (
+          <li key={index}>{formatInputError(err)}</li>
+        ))}
+        {validationIssues.map((issue, index) => (
+          <li key={"v" + index}>{issue.table}: {issue.message}</li>
+        ))}
+      </ul>
       <Grid timeTable={timeTable} setTimeTable={setTimeTable} rooms={rooms} periods={periods}/>
     </div>
   );
@@ -3,6 +3,7 @@ import {invoke} from "@tauri-apps/api/tauri";
 import styles from "./Grid.module.css";
 import { Droppable } from "./Droppable/Droppable";
 import { Draggable } from "./Draggable/Draggable";
+import { Unscheduled } from "./Unscheduled/Unscheduled";
 import { RoomIndex } from "./RoomIndex/RoomIndex";
 import { Period } from "./Period/Period";
 import { DndContext, MouseSensor, PointerSensor, useSensors } from "@dnd-kit/core";
@@ -19,6 +20,8 @@ interface cellsViolations {
   sameTeacherSameTime: Violations[];
   capacityOver: Violations[];
   strabbleDays : Violations[];
+  absentDays: Violations[];
+  roomNotCandidate: Violations[];
 }
 class ActiveCell {
   id: number;
@@ -56,10 +59,20 @@ class BlankCell {
   size?: number;
 }
 
+interface UnscheduledCell {
+  id: number;
+  classIndex: number;
+  className: string;
+  teachers?: string[];
+  size?: number;
+}
+
 export interface TimeTable {
   classList : (ActiveCell| null)[],
   roomSize: number,
-  periodSize: number
+  periodSize: number,
+  // 置ける場所がなく時間割に載らなかった授業
+  unscheduled?: UnscheduledCell[]
 }
 
 
@@ -75,6 +88,7 @@ interface GridProps {
 const Grid: React.FC<GridProps> = ({ timeTable, setTimeTable ,rooms,periods}) => {
   console.log(timeTable)
   const { classList } = timeTable;
+  const unscheduled = timeTable.unscheduled ?? [];
   const sensor = useSensor(PointerSensor, {
     activationConstraint: {
       distance: 5,
@@ -123,8 +137,24 @@ const Grid: React.FC<GridProps> = ({ timeTable, setTimeTable ,rooms,periods}) =>
 
   return (
     <div style={{ width: "100%" }}>
-      <div className={styles["grid-container"]} style={{}}>
       <DndContext onDragEnd={handleDragEnd} onDragOver={handleDragOver} sensors={sensors}>
+      {unscheduled.length > 0 && (
+        <div className={styles["unscheduled-tray"]}>
+          <div>unscheduled: {unscheduled.length}</div>
+          {unscheduled.map((cell) => {
+            return (
+              <Unscheduled
+                key={cell.id}
+                text={cell.className + " " + (cell.teachers ?? []).join(",")}
+                id={cell.id}
+                styles={styles["unscheduled-cell"]}
+                size={cell.size ?? 1}
+              />
+            );
+          })}
+        </div>
+      )}
+      <div className={styles["grid-container"]} style={{}}>
           {
             classList.map((cell, index) => {
               if (cell!=null) {
@@ -157,6 +187,18 @@ const Grid: React.FC<GridProps> = ({ timeTable, setTimeTable ,rooms,periods}) =>
                         return "\nPeriod: " + violation.period + " Rooms: " + violation.rooms.join(",");
                       });
                     }
+                    if (cell.violations.absentDays.length > 0) {
+                      tipMessage += "\nTeacher is absent on this period";
+                      tipMessage += cell.violations.absentDays.map((violation) => {
+                        return "\nPeriod: " + violation.period + " Rooms: " + violation.rooms.join(",");
+                      });
+                    }
+                    if (cell.violations.roomNotCandidate.length > 0) {
+                      tipMessage += "\nRoom is not a candidate for this class";
+                      tipMessage += cell.violations.roomNotCandidate.map((violation) => {
+                        return "\nPeriod: " + violation.period + " Rooms: " + violation.rooms.join(",");
+                      });
+                    }
                   }
                 }
                 return (
@@ -190,8 +232,6 @@ const Grid: React.FC<GridProps> = ({ timeTable, setTimeTable ,rooms,periods}) =>
               )
             })
           }
-          
-      </DndContext>
       {rooms.map((room,index)=>{
         return <RoomIndex key={index} id={index} name={room} styles={styles["grid-cell"]}/>
       })}
@@ -199,6 +239,7 @@ const Grid: React.FC<GridProps> = ({ timeTable, setTimeTable ,rooms,periods}) =>
         return <Period key={index} id={index} name={period} styles={styles["grid-cell"]}/>
       })}
       </div>
+      </DndContext>
     </div>
   );
 };
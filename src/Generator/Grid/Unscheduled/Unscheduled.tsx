@@ -0,0 +1,35 @@
+import { useDraggable } from "@dnd-kit/core";
+
+interface UnscheduledProps {
+  text: string;
+  id: number;
+  styles: string;
+  size: number;
+}
+
+// 置ける場所がなく時間割に載らなかった授業。ドラッグして時間割に置く
+export function Unscheduled({ text, id, styles, size }: UnscheduledProps) {
+  const { attributes, listeners, setNodeRef, transform } = useDraggable({
+    id: id.toString(),
+  });
+  const style = transform
+    ? {
+        transform: `translate3d(${transform.x}px, ${transform.y}px, 0)`,
+        backgroundColor: "#FFDDDD",
+        zIndex: 3,
+      }
+    : {
+        backgroundColor: "#FFDDDD",
+        zIndex: 2,
+      };
+
+  return (
+    <div ref={setNodeRef}
+      {...listeners}
+      {...attributes}
+      style={style} className={styles}
+      >
+        {text} ({size})
+    </div>
+  );
+}
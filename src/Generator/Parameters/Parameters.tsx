@@ -0,0 +1,361 @@
+import { invoke } from "@tauri-apps/api/tauri";
+import { useEffect, useState } from "react";
+
+export interface ConstraintWeights {
+  capacity: number;
+  teacher: number;
+  student: number;
+  absentDays: number;
+  straddleDays: number;
+  collection: number;
+  roomNotCandidate: number;
+  unassigned: number;
+}
+
+export interface LocalSearchSettings {
+  // "off" | "eachIteration" | "endOfRun"
+  mode: string;
+  maxSteps: number;
+}
+
+export interface AnnealingSettings {
+  initialTemperature: number;
+  coolingRate: number;
+  minTemperature: number;
+  movesPerStep: number;
+}
+
+export interface GeneticSettings {
+  populationSize: number;
+  tournamentSize: number;
+  crossoverRate: number;
+  mutationRate: number;
+  eliteCount: number;
+}
+
+export interface PheromoneSettings {
+  // "antSystem" | "iterationBest" | "elitist" | "rankBased" | "antColonySystem"
+  strategy: string;
+  elitistWeight: number;
+  rankSize: number;
+  acsLocalRate: number;
+  acsExploitation: number;
+}
+
+export interface StagnationSettings {
+  // "reset" | "smooth"
+  restart: string;
+  smoothingRate: number;
+  minEntropy: number;
+  cooldown: number;
+}
+
+export interface AcoSettings {
+  numOfAnts: number;
+  q: number;
+  alpha: number;
+  beta: number;
+  rou: number;
+  maxIterations: number;
+  tauMin: number;
+  tauMax: number;
+  antProbRandom: number;
+  superNotChange: number;
+  // 0ならCPUの数
+  numOfThreads: number;
+  // nullなら実行ごとに新しいシードを使う
+  seed: number | null;
+  weights: ConstraintWeights;
+  localSearch: LocalSearchSettings;
+  // "aco" | "simulatedAnnealing" | "genetic"
+  solver: string;
+  annealing: AnnealingSettings;
+  genetic: GeneticSettings;
+  pheromone: PheromoneSettings;
+  stagnation: StagnationSettings;
+  // "random" | "saturation" | "weightedDifficulty"
+  classOrdering: string;
+}
+
+// 入れ子の設定のうち数値の項目を並べる
+const NumberFields = <T extends object>(props: {
+  prefix: string;
+  values: T;
+  onChange: (values: T) => void;
+}) => {
+  return (
+    <>
+      {(Object.keys(props.values) as (keyof T)[]).map((key) => (
+        <label key={String(key)}>
+          {props.prefix + "." + String(key)}
+          <input
+            type="number"
+            value={props.values[key] as number}
+            onChange={(e) => props.onChange({ ...props.values, [key]: Number(e.target.value) })}
+          />
+        </label>
+      ))}
+    </>
+  );
+};
+
+const NESTED_KEYS = ["weights", "localSearch", "solver", "annealing", "genetic", "pheromone", "stagnation", "classOrdering"];
+
+interface ParameterError {
+  field: string;
+  value: string;
+  message: string;
+}
+
+interface ParameterPreset {
+  name: string;
+  settings: AcoSettings;
+}
+
+interface PresetList {
+  presets: ParameterPreset[];
+  selected: string | null;
+}
+
+interface ParametersProps {
+  // データセットを読み込み直したときにプリセットを取り直す
+  datasetKey: string;
+}
+
+const Parameters: React.FC<ParametersProps> = ({ datasetKey }) => {
+  let [settings, SetSettings] = useState(null as AcoSettings | null);
+  let [presets, SetPresets] = useState({ presets: [], selected: null } as PresetList);
+  let [presetName, SetPresetName] = useState("");
+  let [errors, SetErrors] = useState([] as string[]);
+
+  const showErrors = (err: any) => {
+    if (Array.isArray(err)) {
+      SetErrors(err.map((e: ParameterError) => e.field + " = " + e.value + ": " + e.message));
+    } else {
+      SetErrors([String(err)]);
+    }
+  };
+
+  useEffect(() => {
+    invoke<AcoSettings>("handle_get_parameters").then(SetSettings).catch(showErrors);
+    invoke<PresetList>("handle_get_presets").then(SetPresets).catch(showErrors);
+  }, [datasetKey]);
+
+  const applySettings = () => {
+    invoke<AcoSettings>("handle_set_parameters", { settings: settings })
+      .then((res) => {
+        SetSettings(res);
+        SetErrors([]);
+      })
+      .catch(showErrors);
+  };
+  const selectPreset = (name: string) => {
+    invoke<AcoSettings>("handle_select_preset", { name: name })
+      .then((res) => {
+        SetSettings(res);
+        SetPresets({ ...presets, selected: name });
+        SetErrors([]);
+      })
+      .catch(showErrors);
+  };
+  const savePreset = () => {
+    invoke<PresetList>("handle_save_preset", { name: presetName, settings: settings })
+      .then((res) => {
+        SetPresets(res);
+        SetErrors([]);
+      })
+      .catch(showErrors);
+  };
+  const deletePreset = () => {
+    invoke<PresetList>("handle_delete_preset", { name: presetName })
+      .then((res) => {
+        SetPresets(res);
+        SetErrors([]);
+      })
+      .catch(showErrors);
+  };
+
+  if (settings == null) {
+    return <div>{errors.join(", ")}</div>;
+  }
+  return (
+    <div>
+      <select
+        value={presets.selected ?? ""}
+        onChange={(e) => selectPreset(e.target.value)}
+      >
+        <option value="" disabled>
+          preset
+        </option>
+        {presets.presets.map((preset) => (
+          <option key={preset.name} value={preset.name}>
+            {preset.name}
+          </option>
+        ))}
+      </select>
+      <input
+        type="text"
+        placeholder="preset name"
+        value={presetName}
+        onChange={(e) => SetPresetName(e.target.value)}
+      />
+      <button onClick={savePreset}>save preset</button>
+      <button onClick={deletePreset}>delete preset</button>
+      <div>
+        {(Object.keys(settings) as (keyof AcoSettings)[]).filter((key) => !NESTED_KEYS.includes(key)).map((key) => (
+          <label key={key}>
+            {key}
+            <input
+              type="number"
+              value={(settings[key] as number | null) ?? ""}
+              onChange={(e) =>
+                SetSettings({
+                  ...settings,
+                  [key]: key == "seed" && e.target.value == "" ? null : Number(e.target.value),
+                })
+              }
+            />
+          </label>
+        ))}
+        <NumberFields
+          prefix="weights"
+          values={settings.weights}
+          onChange={(weights) => SetSettings({ ...settings, weights: weights })}
+        />
+        <label>
+          localSearch
+          <select
+            value={settings.localSearch.mode}
+            onChange={(e) =>
+              SetSettings({
+                ...settings,
+                localSearch: { ...settings.localSearch, mode: e.target.value },
+              })
+            }
+          >
+            <option value="off">off</option>
+            <option value="eachIteration">each iteration</option>
+            <option value="endOfRun">end of run</option>
+          </select>
+        </label>
+        <label>
+          localSearch.maxSteps
+          <input
+            type="number"
+            value={settings.localSearch.maxSteps}
+            onChange={(e) =>
+              SetSettings({
+                ...settings,
+                localSearch: { ...settings.localSearch, maxSteps: Number(e.target.value) },
+              })
+            }
+          />
+        </label>
+        <label>
+          solver
+          <select
+            value={settings.solver}
+            onChange={(e) => SetSettings({ ...settings, solver: e.target.value })}
+          >
+            <option value="aco">ACO</option>
+            <option value="simulatedAnnealing">simulated annealing</option>
+            <option value="genetic">genetic algorithm</option>
+          </select>
+        </label>
+        {settings.solver == "aco" && (
+          <>
+            <label>
+              classOrdering
+              <select
+                value={settings.classOrdering}
+                onChange={(e) => SetSettings({ ...settings, classOrdering: e.target.value })}
+              >
+                <option value="random">random</option>
+                <option value="saturation">saturation (DSatur)</option>
+                <option value="weightedDifficulty">weighted by difficulty</option>
+              </select>
+            </label>
+            <label>
+              pheromone.strategy
+              <select
+                value={settings.pheromone.strategy}
+                onChange={(e) =>
+                  SetSettings({
+                    ...settings,
+                    pheromone: { ...settings.pheromone, strategy: e.target.value },
+                  })
+                }
+              >
+                <option value="antSystem">ant system</option>
+                <option value="iterationBest">iteration best</option>
+                <option value="elitist">elitist</option>
+                <option value="rankBased">rank based</option>
+                <option value="antColonySystem">ant colony system</option>
+              </select>
+            </label>
+            <NumberFields
+              prefix="pheromone"
+              values={{
+                elitistWeight: settings.pheromone.elitistWeight,
+                rankSize: settings.pheromone.rankSize,
+                acsLocalRate: settings.pheromone.acsLocalRate,
+                acsExploitation: settings.pheromone.acsExploitation,
+              }}
+              onChange={(values) =>
+                SetSettings({ ...settings, pheromone: { ...settings.pheromone, ...values } })
+              }
+            />
+            <label>
+              stagnation.restart
+              <select
+                value={settings.stagnation.restart}
+                onChange={(e) =>
+                  SetSettings({
+                    ...settings,
+                    stagnation: { ...settings.stagnation, restart: e.target.value },
+                  })
+                }
+              >
+                <option value="reset">reset</option>
+                <option value="smooth">smooth</option>
+              </select>
+            </label>
+            <NumberFields
+              prefix="stagnation"
+              values={{
+                smoothingRate: settings.stagnation.smoothingRate,
+                minEntropy: settings.stagnation.minEntropy,
+                cooldown: settings.stagnation.cooldown,
+              }}
+              onChange={(values) =>
+                SetSettings({ ...settings, stagnation: { ...settings.stagnation, ...values } })
+              }
+            />
+          </>
+        )}
+        {settings.solver == "simulatedAnnealing" && (
+          <NumberFields
+            prefix="annealing"
+            values={settings.annealing}
+            onChange={(annealing) => SetSettings({ ...settings, annealing: annealing })}
+          />
+        )}
+        {settings.solver == "genetic" && (
+          <NumberFields
+            prefix="genetic"
+            values={settings.genetic}
+            onChange={(genetic) => SetSettings({ ...settings, genetic: genetic })}
+          />
+        )}
+        <button onClick={applySettings}>apply parameters</button>
+      </div>
+      <ul>
+        {errors.map((err, index) => (
+          <li key={index}>{err}</li>
+        ))}
+      </ul>
+    </div>
+  );
+};
+
+export default Parameters;
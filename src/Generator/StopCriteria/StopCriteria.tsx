@@ -0,0 +1,69 @@
+import { invoke } from "@tauri-apps/api/tauri";
+import { useEffect, useState } from "react";
+
+interface StopCriteriaValues {
+  timeLimitSecs: number | null;
+  maxIterations: number | null;
+  targetScore: number | null;
+  stopOnNoHardViolations: boolean;
+  maxIterationsWithoutImprovement: number | null;
+}
+
+// 空欄はその条件を使わない
+const toNumber = (value: string) => (value == "" ? null : Number(value));
+
+const StopCriteria: React.FC = () => {
+  let [criteria, SetCriteria] = useState(null as StopCriteriaValues | null);
+  let [error, SetError] = useState("");
+
+  useEffect(() => {
+    invoke<StopCriteriaValues>("handle_get_stop_criteria")
+      .then(SetCriteria)
+      .catch((err) => SetError(String(err)));
+  }, []);
+
+  const apply = () => {
+    invoke<StopCriteriaValues>("handle_set_stop_criteria", { criteria: criteria })
+      .then((res) => {
+        SetCriteria(res);
+        SetError("");
+      })
+      .catch((err) => SetError(String(err)));
+  };
+
+  if (criteria == null) {
+    return <div>{error}</div>;
+  }
+  const numberFields: (keyof StopCriteriaValues)[] = [
+    "timeLimitSecs",
+    "maxIterations",
+    "targetScore",
+    "maxIterationsWithoutImprovement",
+  ];
+  return (
+    <div>
+      {numberFields.map((key) => (
+        <label key={key}>
+          {key}
+          <input
+            type="number"
+            value={(criteria[key] as number | null) ?? ""}
+            onChange={(e) => SetCriteria({ ...criteria, [key]: toNumber(e.target.value) })}
+          />
+        </label>
+      ))}
+      <label>
+        stopOnNoHardViolations
+        <input
+          type="checkbox"
+          checked={criteria.stopOnNoHardViolations}
+          onChange={(e) => SetCriteria({ ...criteria, stopOnNoHardViolations: e.target.checked })}
+        />
+      </label>
+      <button onClick={apply}>apply stop criteria</button>
+      <div>{error}</div>
+    </div>
+  );
+};
+
+export default StopCriteria;
@@ -7,6 +7,8 @@ interface TableProp {
   columns: Column<any>[];
   data: any[];
   width: number;
+  updateRow: (index: number, row: any) => void;
+  deleteRow: (index: number) => void;
 }
 
 /**
@@ -14,7 +16,7 @@ interface TableProp {
  * @param TableProp 引数
  * @returns コンポーネント
  */
-function Table({ columns, data }: TableProp) {
+function Table({ columns, data, updateRow, deleteRow }: TableProp) {
   console.log(columns, data);
   // react-tableの定義
   const { getTableProps, getTableBodyProps, headerGroups, rows, prepareRow } =
@@ -43,8 +45,26 @@ function Table({ columns, data }: TableProp) {
           return (
             <tr {...row.getRowProps()}>
               {row.cells.map((cell) => {
-                return <td {...cell.getCellProps()}>{cell.value}</td>;
+                return (
+                  <td {...cell.getCellProps()}>
+                    <input
+                      defaultValue={cell.value}
+                      onBlur={(e) => {
+                        if (e.target.value == cell.value) {
+                          return;
+                        }
+                        updateRow(row.index, {
+                          ...row.original,
+                          [cell.column.id]: e.target.value,
+                        });
+                      }}
+                    />
+                  </td>
+                );
               })}
+              <td>
+                <button onClick={() => deleteRow(row.index)}>delete</button>
+              </td>
             </tr>
           );
         })}
@@ -32,25 +32,65 @@ const TableEditor: React.FC<TableEditorProps> = (props: TableEditorProps) => {
 
   const [table, setTable] = useState(defaultTableData);
 
-  useEffect(() => {
+  const [errors, setErrors] = useState([] as string[]);
+
+  const applyTable = (res: any) => {
+    let columns = res.columns as any[];
+    let data = res.data as any[];
+    for (let i = 0; i < columns.length; i++) {
+      columns[i].Header = columns[i].header;
+    }
+    setTable({ columns, data });
+    setErrors([]);
+  };
+  const showErrors = (err: any) => {
+    if (Array.isArray(err)) {
+      setErrors(err.map((e) => e.file + ":" + (e.line ?? "") + " " + (e.column ?? "") + " " + e.message + (e.suggestion ? " did you mean " + e.suggestion + "?" : "")));
+    } else {
+      setErrors([String(err)]);
+    }
+  };
+
+  // 読み込み済みの入力データから表を作る
+  const loadTable = () => {
     invoke<any>("handle_get_table", { tableType: props.tableName })
-      .then((res) => {
-        let { columns, data } = Object.keys(res).map((key) => ({
-          columns: res[key].columns as any[],
-          data: res[key].data as any[],
-        }))[0];
-        for (let i = 0; i < columns.length; i++) {
-          columns[i].Header = columns[i].header;
-        }
-        setTable({ columns, data });
-      })
-      .catch((err) => {
-        console.log(err, props.tableName);
-      });
+      .then(applyTable)
+      .catch(showErrors);
+  };
+
+  useEffect(() => {
+    loadTable();
   }, []);
+
+  const addRow = () => {
+    let row: { [key: string]: string } = {};
+    for (const column of table.columns) {
+      row[column.accessor as string] = "";
+    }
+    invoke<any>("handle_add_row", { tableType: props.tableName, row: row })
+      .then(applyTable)
+      .catch(showErrors);
+  };
+  const updateRow = (index: number, row: any) => {
+    invoke<any>("handle_update_row", { tableType: props.tableName, index: index, row: row })
+      .then(applyTable)
+      .catch(showErrors);
+  };
+  const deleteRow = (index: number) => {
+    invoke<any>("handle_delete_row", { tableType: props.tableName, index: index })
+      .then(applyTable)
+      .catch(showErrors);
+  };
   // テーブルに表示する列の定義（CSVの１行目から作成）
   return (
     <div className={styles.field}>
+      <button onClick={loadTable}>reload</button>
+      <button onClick={addRow}>add row</button>
+      <ul>
+        {errors.map((err, index) => (
+          <li key={index}>{err}</li>
+        ))}
+      </ul>
       <div style={{ height: "100vh", overflow: "scroll" }}>
         <div style={{ height: "100%" }}>
           <Table
@@ -58,6 +98,8 @@ const TableEditor: React.FC<TableEditorProps> = (props: TableEditorProps) => {
             data={table.data}
             width={660}
             tableName={props.tableName}
+            updateRow={updateRow}
+            deleteRow={deleteRow}
           />
         </div>
       </div>